@@ -0,0 +1,219 @@
+use bevy::{input::mouse::MouseMotion, prelude::*, transform::TransformSystem};
+
+use client::chunk::VoxelStore;
+use client::origin::WorldOrigin;
+use client::physics::raycast;
+
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use super::input::{Action, InputMap, StickInput};
+use super::player::{PlayerController, PlayerModel, EYE_HEIGHT};
+use crate::world::origin::place_players;
+use crate::world::state::GameState;
+
+const RADIANT: f32 = PI / 180.;
+const PITCH_LIMIT: f32 = FRAC_PI_2 - 0.01;
+
+/// Full right stick deflection turn rate, in degrees per second.
+const STICK_TURN_RATE: f32 = 180.;
+
+/// Gap kept between the camera and the block it collided with.
+const CAMERA_MARGIN: f32 = 0.2;
+
+/// Look direction of the player, the transform rotation is rebuilt from it
+/// every frame so pitch can be clamped and never rolls.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct CameraLook {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl CameraLook {
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = (self.yaw + yaw).rem_euclid(TAU);
+        self.pitch = (self.pitch + pitch).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    }
+
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    FirstPerson,
+    ThirdPerson,
+    Orbit,
+}
+
+impl Default for CameraMode {
+    fn default() -> Self {
+        Self::FirstPerson
+    }
+}
+
+/// Camera rig following the player body.
+#[derive(Component)]
+pub struct PlayerCamera {
+    /// Look used in orbit mode, independent from the player one.
+    pub orbit: CameraLook,
+    pub distance: f32,
+}
+
+impl Default for PlayerCamera {
+    fn default() -> Self {
+        Self {
+            orbit: default(),
+            distance: 4.,
+        }
+    }
+}
+
+pub fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(Camera3dBundle::default())
+        .insert(PlayerCamera::default());
+}
+
+/// Mouse motion is a distance and is applied as is, only the stick turn rate
+/// is scaled by the frame time.
+pub fn camera_look(
+    mut mouse: EventReader<MouseMotion>,
+    sticks: Res<StickInput>,
+    map: Res<InputMap>,
+    mode: Res<CameraMode>,
+    time: Res<Time>,
+    mut players: Query<(&mut Transform, &mut CameraLook), With<PlayerController>>,
+    mut cameras: Query<&mut PlayerCamera>,
+) {
+    let mut delta = Vec2::ZERO;
+
+    for event in mouse.iter() {
+        delta += event.delta;
+    }
+
+    let mut turn = -delta * map.mouse.sensitivity * RADIANT;
+
+    if map.mouse.invert_y {
+        turn.y = -turn.y;
+    }
+
+    turn += sticks.look * STICK_TURN_RATE * RADIANT * time.delta_seconds() * Vec2::new(-1., 1.);
+
+    if *mode == CameraMode::Orbit {
+        for mut camera in cameras.iter_mut() {
+            camera.orbit.rotate(turn.x, turn.y);
+        }
+
+        return;
+    }
+
+    for (mut transform, mut look) in players.iter_mut() {
+        look.rotate(turn.x, turn.y);
+        transform.rotation = Quat::from_rotation_y(look.yaw);
+    }
+}
+
+pub fn cycle_camera_mode(
+    actions: Res<Input<Action>>,
+    mut mode: ResMut<CameraMode>,
+    players: Query<&CameraLook, With<PlayerController>>,
+    mut cameras: Query<&mut PlayerCamera>,
+) {
+    if !actions.just_pressed(Action::CycleCamera) {
+        return;
+    }
+
+    *mode = match *mode {
+        CameraMode::FirstPerson => CameraMode::ThirdPerson,
+        CameraMode::ThirdPerson => CameraMode::Orbit,
+        CameraMode::Orbit => CameraMode::FirstPerson,
+    };
+
+    if *mode == CameraMode::Orbit {
+        let look = players.single();
+
+        for mut camera in cameras.iter_mut() {
+            camera.orbit = *look;
+        }
+    }
+}
+
+/// Shows the body out of first person, the player is spawned again with each
+/// world.
+pub fn player_model_visibility(
+    mode: Res<CameraMode>,
+    spawned: Query<(), Added<PlayerModel>>,
+    mut models: Query<&mut Visibility, With<PlayerModel>>,
+) {
+    if !mode.is_changed() && spawned.is_empty() {
+        return;
+    }
+
+    for mut visibility in models.iter_mut() {
+        visibility.is_visible = *mode != CameraMode::FirstPerson;
+    }
+}
+
+/// Places the camera at the eyes, or behind them and pulled in front of any
+/// block in between so it never clips into terrain. The camera stays put
+/// while no world is open.
+pub fn camera_follow(
+    mode: Res<CameraMode>,
+    store: Res<VoxelStore>,
+    origin: Res<WorldOrigin>,
+    players: Query<(&Transform, &CameraLook), (With<PlayerController>, Without<PlayerCamera>)>,
+    mut cameras: Query<(&mut Transform, &PlayerCamera)>,
+) {
+    let (player, look) = match players.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let eye = player.translation + Vec3::Y * EYE_HEIGHT;
+
+    for (mut transform, camera) in cameras.iter_mut() {
+        let rotation = match *mode {
+            CameraMode::FirstPerson => {
+                transform.translation = eye;
+                transform.rotation = look.rotation();
+
+                continue;
+            }
+            CameraMode::ThirdPerson => look.rotation(),
+            CameraMode::Orbit => camera.orbit.rotation(),
+        };
+
+        let back = rotation * Vec3::Z;
+
+        let distance = match raycast(eye, back, camera.distance + CAMERA_MARGIN, |position| {
+            store.is_solid(origin.to_world(position))
+        }) {
+            Some(hit) => (hit.distance - CAMERA_MARGIN).max(0.),
+            None => camera.distance,
+        };
+
+        transform.translation = eye + back * distance;
+        transform.rotation = rotation;
+    }
+}
+
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraMode>()
+            .add_startup_system(spawn_camera)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(camera_look)
+                    .with_system(cycle_camera_mode),
+            )
+            .add_system(player_model_visibility)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_follow
+                    .after(place_players)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
@@ -0,0 +1,333 @@
+use bevy::{prelude::*, text::Font};
+
+use client::chunk::VoxelStore;
+use client::command::{Command, CommandRegistry, Dispatch, GameMode};
+use client::inventory::{Inventory, ItemStack};
+use client::origin::WorldPos;
+use client::time::WorldTime;
+
+use std::collections::VecDeque;
+use std::fs;
+
+use super::input::{Action, StartRebind};
+use super::player::{MovementMode, PlayerBody, PlayerController, PlayerMode};
+use crate::voxel::blocks::asset_path;
+use crate::world::origin::PlayerPosition;
+use crate::world::save::ActiveWorld;
+use crate::world::settings::Settings;
+use crate::world::state::GameState;
+
+const FONT_PATH: &str = "assets/fonts/DejaVuSansMono.ttf";
+const FONT_SIZE: f32 = 18.;
+
+/// Lines kept in the console history.
+const LOG_LINES: usize = 12;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
+
+/// Font of every text of the interface.
+pub struct UiFont(pub Handle<Font>);
+
+impl FromWorld for UiFont {
+    fn from_world(world: &mut World) -> Self {
+        let font = match fs::read(asset_path(FONT_PATH)).map(Font::try_from_bytes) {
+            Ok(Ok(font)) => font,
+            Ok(Err(error)) => {
+                warn!("invalid {}: {}", FONT_PATH, error);
+                return Self(Handle::default());
+            }
+            Err(error) => {
+                warn!("could not read {}: {}", FONT_PATH, error);
+                return Self(Handle::default());
+            }
+        };
+
+        Self(world.resource_mut::<Assets<Font>>().add(font))
+    }
+}
+
+pub struct Console {
+    pub open: bool,
+    /// Line being typed.
+    pub input: String,
+    /// Playing on a server, commands changing the world are sent to it.
+    pub remote: bool,
+    log: VecDeque<String>,
+    registry: CommandRegistry,
+    /// Commands to run on the next update.
+    pending: Vec<Command>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            remote: false,
+            log: VecDeque::new(),
+            registry: CommandRegistry::default(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Console {
+    pub fn log(&mut self, line: impl Into<String>) {
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+
+        self.log.push_back(line.into());
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &String> {
+        self.log.iter()
+    }
+
+    /// Runs or queues a typed line, returns what goes to the server.
+    pub fn submit(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return None;
+        }
+
+        match self.registry.dispatch(line, self.remote) {
+            Ok(Dispatch::Local(command)) => {
+                self.log(line);
+                self.pending.push(command);
+                None
+            }
+            Ok(Dispatch::Forward(line)) => {
+                self.log(line.clone());
+                Some(line)
+            }
+            Ok(Dispatch::Chat(message)) => {
+                self.log(format!("<player> {}", message));
+                Some(message)
+            }
+            Err(error) => {
+                self.log(error.to_string());
+                None
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleRoot;
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub fn spawn_console(mut commands: Commands, font: Res<UiFont>) {
+    let style = TextStyle {
+        font: font.0.clone(),
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(8.),
+                    bottom: Val::Px(80.),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(4.)),
+                ..default()
+            },
+            color: BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .insert(ConsoleRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_sections([
+                    TextSection::new("", style.clone()),
+                    TextSection::new("", style),
+                ]))
+                .insert(ConsoleText);
+        });
+}
+
+/// T opens the console, `/` opens it on a command. Enter runs the line and
+/// Escape leaves it.
+pub fn console_input(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        // the key opening the console is not typed in it
+        characters.iter().last();
+
+        if keys.just_pressed(KeyCode::T) {
+            console.open = true;
+        } else if keys.just_pressed(KeyCode::Slash) {
+            console.open = true;
+            console.input.push('/');
+        }
+
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        console.open = false;
+        console.input.clear();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+
+        // the game can't join a server yet, lines meant for one go nowhere
+        if console.submit(&line).is_some() {
+            console.log("not connected to a server");
+        }
+
+        console.open = false;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    for character in characters.iter() {
+        if !character.char.is_control() {
+            console.input.push(character.char);
+        }
+    }
+}
+
+/// Runs the commands typed in the console and logs how they went.
+#[allow(clippy::too_many_arguments)]
+pub fn run_commands(
+    mut console: ResMut<Console>,
+    store: Res<VoxelStore>,
+    world: Res<ActiveWorld>,
+    mut world_time: ResMut<WorldTime>,
+    mut inventory: ResMut<Inventory>,
+    mut movement: ResMut<MovementMode>,
+    mut rebinds: EventWriter<StartRebind>,
+    mut settings: ResMut<Settings>,
+    mut players: Query<
+        (&mut PlayerPosition, &mut PlayerBody, &mut PlayerMode),
+        With<PlayerController>,
+    >,
+) {
+    if console.pending.is_empty() {
+        return;
+    }
+
+    for command in std::mem::take(&mut console.pending) {
+        let result = match command {
+            Command::Teleport(coordinates) => match players.get_single_mut() {
+                Ok((mut position, mut body, _)) => {
+                    let current = position.0;
+                    let target = [0, 1, 2].map(|axis| {
+                        coordinates[axis]
+                            .resolve(current.block[axis] as f64 + current.offset[axis] as f64)
+                    });
+
+                    let block = target.map(|axis| axis.floor() as i64);
+                    let offset = [0, 1, 2].map(|axis| (target[axis] - block[axis] as f64) as f32);
+
+                    position.0 = WorldPos::new(block, offset.into());
+                    body.velocity = Vec3::ZERO;
+
+                    Ok(format!(
+                        "teleported to {:.1} {:.1} {:.1}",
+                        target[0], target[1], target[2]
+                    ))
+                }
+                Err(_) => Err("no player".to_string()),
+            },
+            Command::Seed => Ok(format!("seed: {}", world.seed)),
+            Command::SetTime(time) => {
+                world_time.time_of_day = time;
+                Ok(format!("time set to {}", time))
+            }
+            Command::Give { block, count } => match store.registry.id(&block) {
+                Some(id) => {
+                    let left = inventory.insert(ItemStack::new(id, count), &store.registry);
+                    Ok(format!("gave {} {}", count - left, block))
+                }
+                None => Err(format!("unknown block {}", block)),
+            },
+            Command::GameMode(mode) => match players.get_single_mut() {
+                Ok((_, mut body, mut player)) => {
+                    player.0 = mode;
+                    *movement = match mode {
+                        GameMode::Spectator => MovementMode::Spectator,
+                        _ => MovementMode::Walking,
+                    };
+
+                    body.velocity = Vec3::ZERO;
+                    body.on_ground = false;
+
+                    Ok(format!("game mode set to {:?}", mode))
+                }
+                Err(_) => Err("no player".to_string()),
+            },
+            Command::Bind(name) => match Action::from_name(&name) {
+                Some(action) => {
+                    rebinds.send(StartRebind(action));
+                    Ok(format!("press the new binding of {:?}", action))
+                }
+                None => Err(format!("unknown action {}", name)),
+            },
+            // saved by `apply_settings` once they changed
+            Command::Set { name, value } => settings
+                .set(&name, &value)
+                .map(|_| format!("{} set to {}", name, value)),
+        };
+
+        match result {
+            Ok(line) | Err(line) => console.log(line),
+        }
+    }
+}
+
+pub fn update_console(
+    console: Res<Console>,
+    mut roots: Query<&mut Style, With<ConsoleRoot>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for mut style in roots.iter_mut() {
+        style.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = console.lines().map(|line| format!("{}\n", line)).collect();
+        text.sections[1].value = format!("> {}_", console.input);
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<UiFont>()
+            .add_startup_system(spawn_console)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(console_input)
+                    .with_system(run_commands),
+            )
+            .add_system(update_console);
+    }
+}
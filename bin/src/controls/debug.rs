@@ -0,0 +1,172 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use client::biome::{elevations, Biome};
+use client::chunk::VoxelStore;
+use client::orientation::Orientation;
+
+use super::camera::CameraLook;
+use super::console::UiFont;
+use super::player::PlayerController;
+use crate::voxel::terrain::{ChunkEntities, ChunkTimings, Timing};
+use crate::world::origin::PlayerPosition;
+
+const FONT_SIZE: f32 = 16.;
+const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
+
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Column the biome was last sampled for, with its biome and elevation.
+    sampled: Option<(IVec2, Biome, f64)>,
+}
+
+#[derive(Component)]
+pub struct DebugRoot;
+
+#[derive(Component)]
+pub struct DebugText;
+
+pub fn spawn_debug_overlay(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(8.),
+                    top: Val::Px(8.),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(4.)),
+                ..default()
+            },
+            color: BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .insert(DebugRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(DebugText);
+        });
+}
+
+/// F3 shows or hides the overlay.
+pub fn toggle_debug_overlay(
+    keys: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut roots: Query<&mut Style, With<DebugRoot>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    overlay.visible = !overlay.visible;
+
+    for mut style in roots.iter_mut() {
+        style.display = if overlay.visible {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn milliseconds(timing: Timing) -> String {
+    timing
+        .average
+        .map_or("-".to_string(), |average| format!("{:.2} ms", average))
+}
+
+pub fn update_debug_overlay(
+    mut overlay: ResMut<DebugOverlay>,
+    diagnostics: Res<Diagnostics>,
+    store: Res<VoxelStore>,
+    chunks: Res<ChunkEntities>,
+    timings: Res<ChunkTimings>,
+    players: Query<(&PlayerPosition, &CameraLook), With<PlayerController>>,
+    mut texts: Query<&mut Text, With<DebugText>>,
+) {
+    if !overlay.visible {
+        return;
+    }
+
+    let (position, look) = match players.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let block = match position.0.block_pos() {
+        Some(block) => block,
+        None => return,
+    };
+    let column = IVec2::new(block.0.x, block.0.z);
+
+    // the planet graph is rebuilt on every sample, only do it on a new column
+    let (biome, elevation) = match overlay.sampled {
+        Some((sampled, biome, elevation)) if sampled == column => (biome, elevation),
+        _ => {
+            let elevation = elevations(&[column])[0];
+            let biome = Biome::from_elevation(elevation);
+
+            overlay.sampled = Some((column, biome, elevation));
+            (biome, elevation)
+        }
+    };
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.);
+
+    let [x, y, z] =
+        [0, 1, 2].map(|axis| position.0.block[axis] as f64 + position.0.offset[axis] as f64);
+    let chunk = block.chunk().0;
+    let facing = Orientation::facing(look.rotation() * Vec3::NEG_Z);
+    let ground = store
+        .surface(column)
+        .map_or("-".to_string(), |y| y.to_string());
+
+    let lines = [
+        format!("fps: {:.0}", fps),
+        format!("position: {:.2} {:.2} {:.2}", x, y, z),
+        format!("block: {} {} {}", block.0.x, block.0.y, block.0.z),
+        format!("chunk: {} {}", chunk.x, chunk.y),
+        format!("facing: {:?}", facing),
+        format!("biome: {:?}, elevation {:.3}", biome, elevation),
+        format!("ground: {}", ground),
+        format!("chunks: {} spawned, {} loaded", chunks.0.len(), store.len()),
+        format!(
+            "generation: {}, meshing: {}",
+            milliseconds(timings.generation),
+            milliseconds(timings.meshing)
+        ),
+    ];
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .init_resource::<UiFont>()
+            .init_resource::<DebugOverlay>()
+            .add_startup_system(spawn_debug_overlay)
+            .add_system(toggle_debug_overlay)
+            .add_system(update_debug_overlay);
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+
+use client::block::BlockRegistry;
+use client::chunk::VoxelStore;
+use client::inventory::{Inventory, ItemStack, HOTBAR_SIZE};
+
+use super::console::Console;
+use super::input::Action;
+use crate::world::state::{despawn_screen, GameState};
+
+const SLOT_SIZE: f32 = 48.;
+const SLOT_MARGIN: f32 = 4.;
+
+const SLOT_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.6);
+const SELECTED_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 0.8);
+
+/// Blocks a new world starts with.
+const STARTER_KIT: [&str; 6] = ["torch", "log", "furnace", "stairs", "slab", "fence"];
+
+#[derive(Component)]
+pub struct HotbarRoot;
+
+/// Background of a hotbar slot.
+#[derive(Component)]
+pub struct HotbarSlot(pub usize);
+
+/// Square showing the color of the block held in a slot.
+#[derive(Component)]
+pub struct HotbarItem(pub usize);
+
+/// Inventory of a world without a saved one.
+pub fn starter_inventory(registry: &BlockRegistry) -> Inventory {
+    let mut inventory = Inventory::default();
+
+    for name in STARTER_KIT {
+        if let Some(block) = registry.id(name) {
+            let max = registry.get(block).max_stack;
+            inventory.insert(ItemStack::new(block, max), registry);
+        }
+    }
+
+    inventory
+}
+
+pub fn spawn_hotbar(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Px(SLOT_SIZE + 4. * SLOT_MARGIN)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(0.),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(HotbarRoot)
+        .with_children(|parent| {
+            for slot in 0..HOTBAR_SIZE {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(SLOT_SIZE), Val::Px(SLOT_SIZE)),
+                            margin: UiRect::all(Val::Px(SLOT_MARGIN)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: SLOT_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(HotbarSlot(slot))
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size::new(
+                                        Val::Px(SLOT_SIZE * 0.6),
+                                        Val::Px(SLOT_SIZE * 0.6),
+                                    ),
+                                    ..default()
+                                },
+                                color: Color::NONE.into(),
+                                ..default()
+                            })
+                            .insert(HotbarItem(slot));
+                    });
+            }
+        });
+}
+
+/// Hotbar actions pick a slot, the wheel steps through them.
+pub fn select_slot(
+    actions: Res<Input<Action>>,
+    mut wheel: EventReader<MouseWheel>,
+    console: Res<Console>,
+    mut inventory: ResMut<Inventory>,
+) {
+    let scrolled: f32 = wheel.iter().map(|event| event.y).sum();
+
+    if console.open {
+        return;
+    }
+
+    for (slot, action) in Action::HOTBAR.into_iter().enumerate() {
+        if actions.just_pressed(action) {
+            inventory.select(slot);
+        }
+    }
+
+    // scrolling up moves left, like most games
+    if scrolled != 0. {
+        inventory.scroll(-scrolled.signum() as i32);
+    }
+}
+
+/// Colors the slots after the inventory, the hotbar is spawned once the game
+/// starts.
+pub fn update_hotbar(
+    inventory: Res<Inventory>,
+    store: Res<VoxelStore>,
+    spawned: Query<(), Added<HotbarSlot>>,
+    mut slots: Query<(&HotbarSlot, &mut UiColor), Without<HotbarItem>>,
+    mut items: Query<(&HotbarItem, &mut UiColor), Without<HotbarSlot>>,
+) {
+    if !inventory.is_changed() && !store.is_changed() && spawned.is_empty() {
+        return;
+    }
+
+    for (slot, mut color) in slots.iter_mut() {
+        *color = if slot.0 == inventory.selected() {
+            SELECTED_COLOR.into()
+        } else {
+            SLOT_COLOR.into()
+        };
+    }
+
+    for (item, mut color) in items.iter_mut() {
+        *color = match inventory.get(item.0) {
+            Some(stack) => {
+                let [r, g, b] = store.registry.get(stack.block).color;
+                Color::rgb(r, g, b).into()
+            }
+            None => Color::NONE.into(),
+        };
+    }
+}
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Playing).with_system(spawn_hotbar))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(select_slot))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing).with_system(despawn_screen::<HotbarRoot>),
+            )
+            .add_system(update_hotbar);
+    }
+}
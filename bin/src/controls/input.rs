@@ -0,0 +1,447 @@
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+use client::inventory::HOTBAR_SIZE;
+
+use super::console::Console;
+use crate::world::settings::Settings;
+
+const BINDINGS_PATH: &str = "config/bindings.ron";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    Jump,
+    Descend,
+    Break,
+    Place,
+    ToggleSpectator,
+    CycleCamera,
+    HotbarSlot1,
+    HotbarSlot2,
+    HotbarSlot3,
+    HotbarSlot4,
+    HotbarSlot5,
+    HotbarSlot6,
+    HotbarSlot7,
+    HotbarSlot8,
+    HotbarSlot9,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Jump,
+        Action::Descend,
+        Action::Break,
+        Action::Place,
+        Action::ToggleSpectator,
+        Action::CycleCamera,
+        Action::HotbarSlot1,
+        Action::HotbarSlot2,
+        Action::HotbarSlot3,
+        Action::HotbarSlot4,
+        Action::HotbarSlot5,
+        Action::HotbarSlot6,
+        Action::HotbarSlot7,
+        Action::HotbarSlot8,
+        Action::HotbarSlot9,
+    ];
+
+    /// Action named as in the settings file, in any case.
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL
+            .into_iter()
+            .find(|action| format!("{:?}", action).eq_ignore_ascii_case(name))
+    }
+
+    /// Selects the hotbar slot of the same index.
+    pub const HOTBAR: [Action; HOTBAR_SIZE] = [
+        Action::HotbarSlot1,
+        Action::HotbarSlot2,
+        Action::HotbarSlot3,
+        Action::HotbarSlot4,
+        Action::HotbarSlot5,
+        Action::HotbarSlot6,
+        Action::HotbarSlot7,
+        Action::HotbarSlot8,
+        Action::HotbarSlot9,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum KeyboardLayout {
+    Azerty,
+    Qwerty,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickSettings {
+    /// Radial deadzone, as a fraction of full deflection.
+    pub deadzone: f32,
+    /// Response curve exponent applied after the deadzone, 1 is linear.
+    pub exponent: f32,
+    pub invert_look_y: bool,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            exponent: 2.,
+            invert_look_y: false,
+        }
+    }
+}
+
+impl StickSettings {
+    pub fn shape(&self, raw: Vec2) -> Vec2 {
+        let length = raw.length();
+
+        if length <= self.deadzone {
+            return Vec2::ZERO;
+        }
+
+        let scaled = ((length - self.deadzone) / (1. - self.deadzone)).min(1.);
+
+        raw / length * scaled.powf(self.exponent)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseSettings {
+    /// Degrees turned per pixel of mouse motion.
+    pub sensitivity: f32,
+    pub invert_y: bool,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.15,
+            invert_y: false,
+        }
+    }
+}
+
+/// Bindings file layout: a preset plus per-action overrides.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BindingsConfig {
+    pub layout: KeyboardLayout,
+    #[serde(default)]
+    pub bindings: HashMap<Action, Vec<Binding>>,
+    #[serde(default)]
+    pub sticks: StickSettings,
+    #[serde(default)]
+    pub mouse: MouseSettings,
+}
+
+impl Default for BindingsConfig {
+    fn default() -> Self {
+        InputMap::default().to_config()
+    }
+}
+
+pub struct InputMap {
+    pub layout: KeyboardLayout,
+    pub sticks: StickSettings,
+    pub mouse: MouseSettings,
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl InputMap {
+    pub fn preset(layout: KeyboardLayout) -> Self {
+        let (forward, left) = match layout {
+            KeyboardLayout::Azerty => (KeyCode::Z, KeyCode::Q),
+            KeyboardLayout::Qwerty => (KeyCode::W, KeyCode::A),
+        };
+
+        let slot_keys = [
+            KeyCode::Key1,
+            KeyCode::Key2,
+            KeyCode::Key3,
+            KeyCode::Key4,
+            KeyCode::Key5,
+            KeyCode::Key6,
+            KeyCode::Key7,
+            KeyCode::Key8,
+            KeyCode::Key9,
+        ];
+
+        let mut bindings = HashMap::from_iter([
+            (Action::MoveForward, vec![Binding::Key(forward)]),
+            (Action::MoveBackward, vec![Binding::Key(KeyCode::S)]),
+            (Action::StrafeLeft, vec![Binding::Key(left)]),
+            (Action::StrafeRight, vec![Binding::Key(KeyCode::D)]),
+            (
+                Action::Jump,
+                vec![
+                    Binding::Key(KeyCode::Space),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                Action::Descend,
+                vec![
+                    Binding::Key(KeyCode::LShift),
+                    Binding::Gamepad(GamepadButtonType::East),
+                ],
+            ),
+            (
+                Action::Break,
+                vec![
+                    Binding::Mouse(MouseButton::Left),
+                    Binding::Gamepad(GamepadButtonType::RightTrigger2),
+                ],
+            ),
+            (
+                Action::Place,
+                vec![
+                    Binding::Mouse(MouseButton::Right),
+                    Binding::Gamepad(GamepadButtonType::LeftTrigger2),
+                ],
+            ),
+            (
+                Action::ToggleSpectator,
+                vec![
+                    Binding::Key(KeyCode::V),
+                    Binding::Gamepad(GamepadButtonType::North),
+                ],
+            ),
+            (
+                Action::CycleCamera,
+                vec![
+                    Binding::Key(KeyCode::F5),
+                    Binding::Gamepad(GamepadButtonType::Select),
+                ],
+            ),
+        ]);
+
+        for (action, key) in Action::HOTBAR.into_iter().zip(slot_keys) {
+            bindings.insert(action, vec![Binding::Key(key)]);
+        }
+
+        Self {
+            layout,
+            sticks: default(),
+            mouse: default(),
+            bindings,
+        }
+    }
+
+    pub fn from_config(config: BindingsConfig) -> Self {
+        let mut map = Self::preset(config.layout);
+        map.bindings.extend(config.bindings);
+        map.sticks = config.sticks;
+        map.mouse = config.mouse;
+
+        map
+    }
+
+    /// Only the bindings that differ from the layout preset are written out.
+    pub fn to_config(&self) -> BindingsConfig {
+        let preset = Self::preset(self.layout);
+
+        BindingsConfig {
+            layout: self.layout,
+            bindings: self
+                .bindings
+                .iter()
+                .filter(|(action, bindings)| preset.bindings.get(*action) != Some(*bindings))
+                .map(|(action, bindings)| (*action, bindings.clone()))
+                .collect(),
+            sticks: self.sticks,
+            mouse: self.mouse,
+        }
+    }
+
+    /// Bindings of the file older versions saved them in, before settings.
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(BINDINGS_PATH) {
+            Ok(text) => text,
+            Err(_) => return Self::default(),
+        };
+
+        match ron::from_str(&text) {
+            Ok(config) => Self::from_config(config),
+            Err(error) => {
+                warn!("invalid {}: {}", BINDINGS_PATH, error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.bindings.insert(action, vec![binding]);
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::preset(KeyboardLayout::Azerty)
+    }
+}
+
+/// Send to make the next key or mouse button pressed the new binding of the
+/// action, as `/bind` does.
+pub struct StartRebind(pub Action);
+
+#[derive(Default)]
+pub struct PendingRebind(Option<Action>);
+
+/// Analog stick state after deadzone and response curve, x right and y up.
+#[derive(Default)]
+pub struct StickInput {
+    pub movement: Vec2,
+    pub look: Vec2,
+}
+
+pub fn update_actions(
+    map: Res<InputMap>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    pad_buttons: Res<Input<GamepadButton>>,
+    console: Res<Console>,
+    mut actions: ResMut<Input<Action>>,
+) {
+    actions.clear();
+
+    // keys typed in the console do not move the player
+    if console.open {
+        for action in Action::ALL {
+            actions.release(action);
+        }
+
+        return;
+    }
+
+    for action in Action::ALL {
+        let pressed = map.bindings(action).iter().any(|binding| match binding {
+            Binding::Key(key) => keys.pressed(*key),
+            Binding::Mouse(button) => buttons.pressed(*button),
+            Binding::Gamepad(button) => gamepads
+                .iter()
+                .any(|gamepad| pad_buttons.pressed(GamepadButton(*gamepad, *button))),
+        });
+
+        if pressed {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+pub fn update_sticks(
+    map: Res<InputMap>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut sticks: ResMut<StickInput>,
+) {
+    let gamepad = match gamepads.iter().next() {
+        Some(gamepad) => *gamepad,
+        None => {
+            *sticks = StickInput::default();
+            return;
+        }
+    };
+
+    let read = |x, y| {
+        Vec2::new(
+            axes.get(GamepadAxis(gamepad, x)).unwrap_or(0.),
+            axes.get(GamepadAxis(gamepad, y)).unwrap_or(0.),
+        )
+    };
+
+    sticks.movement = map.sticks.shape(read(
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+    ));
+    sticks.look = map.sticks.shape(read(
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+    ));
+
+    if map.sticks.invert_look_y {
+        sticks.look.y = -sticks.look.y;
+    }
+}
+
+pub fn capture_rebind(
+    mut requests: EventReader<StartRebind>,
+    mut pending: ResMut<PendingRebind>,
+    mut map: ResMut<InputMap>,
+    mut settings: ResMut<Settings>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    pad_buttons: Res<Input<GamepadButton>>,
+) {
+    if let Some(StartRebind(action)) = requests.iter().last() {
+        pending.0 = Some(*action);
+        return;
+    }
+
+    let action = match pending.0 {
+        Some(action) => action,
+        None => return,
+    };
+
+    let binding = if let Some(key) = keys.get_just_pressed().next() {
+        Binding::Key(*key)
+    } else if let Some(button) = buttons.get_just_pressed().next() {
+        Binding::Mouse(*button)
+    } else if let Some(button) = pad_buttons.get_just_pressed().next() {
+        Binding::Gamepad(button.1)
+    } else {
+        return;
+    };
+
+    // saved with the other settings
+    map.rebind(action, binding);
+    settings.controls = map.to_config();
+
+    pending.0 = None;
+}
+
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        let map = match app.world.get_resource::<Settings>() {
+            Some(settings) => InputMap::from_config(settings.controls.clone()),
+            None => InputMap::default(),
+        };
+
+        app.insert_resource(map)
+            .init_resource::<Input<Action>>()
+            .init_resource::<PendingRebind>()
+            .init_resource::<StickInput>()
+            .add_event::<StartRebind>()
+            .add_system_to_stage(CoreStage::PreUpdate, capture_rebind.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, update_actions.after(InputSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, update_sticks.after(InputSystem));
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use client::block::BlockId;
+use client::chunk::VoxelStore;
+use client::command::GameMode;
+use client::coords::BlockPos;
+use client::inventory::{Inventory, ItemStack};
+use client::origin::WorldOrigin;
+use client::physics::{raycast, Aabb};
+use client::state::{placement, BlockState};
+
+use super::camera::CameraLook;
+use super::input::Action;
+use super::player::{PlayerBody, PlayerController, PlayerMode, EYE_HEIGHT};
+use crate::voxel::terrain::ChunkEntities;
+use crate::world::state::GameState;
+
+const REACH: f32 = 5.;
+
+pub fn break_and_place(
+    mut commands: Commands,
+    actions: Res<Input<Action>>,
+    mut store: ResMut<VoxelStore>,
+    mut inventory: ResMut<Inventory>,
+    origin: Res<WorldOrigin>,
+    chunks: Res<ChunkEntities>,
+    players: Query<(&Transform, &CameraLook, &PlayerMode), With<PlayerController>>,
+) {
+    let breaking = actions.just_pressed(Action::Break);
+    let placing = actions.just_pressed(Action::Place);
+
+    if !breaking && !placing {
+        return;
+    }
+
+    let (transform, look, mode) = players.single();
+    let eye = transform.translation + Vec3::Y * EYE_HEIGHT;
+
+    let direction = look.rotation() * Vec3::NEG_Z;
+
+    let hit = match raycast(eye, direction, REACH, |position| {
+        store
+            .block(origin.to_world(position))
+            .map_or(false, |block| block != BlockId::AIR)
+    }) {
+        Some(hit) => hit,
+        None => return,
+    };
+
+    let (position, block, state) = if breaking {
+        let broken = store
+            .block(origin.to_world(hit.block))
+            .unwrap_or(BlockId::AIR);
+
+        // blocks are only broken into an inventory that can take them,
+        // creative players keep breaking with a full one
+        if mode.0 != GameMode::Creative && !inventory.has_room(broken, &store.registry) {
+            return;
+        }
+
+        (hit.block, BlockId::AIR, BlockState::default())
+    } else {
+        let position = hit.block + hit.normal;
+
+        if store.block(origin.to_world(position)) != Some(BlockId::AIR) {
+            return;
+        }
+
+        let block = match inventory.held() {
+            Some(stack) => stack.block,
+            None => return,
+        };
+
+        let cell = Aabb::new(position.as_vec3(), position.as_vec3() + Vec3::ONE);
+
+        if store.registry.get(block).solid
+            && PlayerBody::aabb(transform.translation).intersects(&cell)
+        {
+            return;
+        }
+
+        let height = eye.y + direction.y * hit.distance;
+        let properties = store.registry.get(block).states;
+
+        (
+            position,
+            block,
+            placement(properties, direction, hit.normal, height),
+        )
+    };
+
+    let position = origin.to_world(position);
+
+    let replaced = match store.replace_state(position, block, state) {
+        Some(replaced) => replaced,
+        None => return,
+    };
+
+    // broken blocks go to the inventory, placed ones come out of the hand
+    if breaking {
+        inventory.insert(ItemStack::new(replaced, 1), &store.registry);
+    } else if mode.0 != GameMode::Creative {
+        let selected = inventory.selected();
+        inventory.remove(selected, 1);
+    }
+
+    chunks.remesh_around(&mut commands, BlockPos(position).chunk().0);
+}
+
+pub struct BlockInteractionPlugin;
+
+impl Plugin for BlockInteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(break_and_place));
+    }
+}
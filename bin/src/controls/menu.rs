@@ -0,0 +1,416 @@
+use bevy::{app::AppExit, prelude::*};
+
+use client::block::BlockRegistry;
+use client::chunk::VoxelStore;
+
+use super::console::{console_input, Console, UiFont};
+use crate::world::save::{
+    list_worlds, open_world, parse_seed, world_name, LevelData, MAX_NAME_LENGTH,
+};
+use crate::world::state::{despawn_screen, GameState};
+
+const TITLE_SIZE: f32 = 48.;
+const FONT_SIZE: f32 = 20.;
+
+const BUTTON_WIDTH: f32 = 320.;
+const BUTTON_HEIGHT: f32 = 40.;
+
+/// Longest seed that can be typed.
+const MAX_SEED_LENGTH: usize = 20;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+const BUTTON_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const HOVERED_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
+const ERROR_COLOR: Color = Color::rgb(1., 0.4, 0.4);
+
+#[derive(Component)]
+pub struct MainMenuScreen;
+
+#[derive(Component)]
+pub struct WorldSelectScreen;
+
+#[derive(Component)]
+pub struct PauseScreen;
+
+/// What a menu button does when clicked.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub enum MenuButton {
+    Play,
+    Quit,
+    OpenWorld(String),
+    CreateWorld,
+    Back,
+    Resume,
+    /// Saves the world and goes back to the main menu.
+    SaveAndQuit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FormField {
+    Name,
+    Seed,
+}
+
+impl Default for FormField {
+    fn default() -> Self {
+        Self::Name
+    }
+}
+
+/// New world being typed on the world select screen, Tab switches between
+/// its fields.
+#[derive(Default)]
+pub struct WorldForm {
+    pub name: String,
+    pub seed: String,
+    pub field: FormField,
+    /// Why the last world could not be created.
+    pub error: Option<String>,
+}
+
+impl WorldForm {
+    fn describe(&self) -> String {
+        let cursor = |field| if self.field == field { "_" } else { "" };
+
+        format!(
+            "name: {}{}\nseed: {}{}",
+            self.name,
+            cursor(FormField::Name),
+            self.seed,
+            cursor(FormField::Seed)
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct WorldFormText;
+
+fn text_style(font: &UiFont, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: font.0.clone(),
+        font_size,
+        color,
+    }
+}
+
+/// Full screen column, children are stacked from the top.
+fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: BACKGROUND_COLOR.into(),
+        ..default()
+    }
+}
+
+fn spawn_title(parent: &mut ChildBuilder, font: &UiFont, title: &str) {
+    parent.spawn_bundle(
+        TextBundle::from_section(title, text_style(font, TITLE_SIZE, Color::WHITE)).with_style(
+            Style {
+                margin: UiRect::all(Val::Px(16.)),
+                ..default()
+            },
+        ),
+    );
+}
+
+fn spawn_button(parent: &mut ChildBuilder, font: &UiFont, label: &str, button: MenuButton) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(BUTTON_WIDTH), Val::Px(BUTTON_HEIGHT)),
+                margin: UiRect::all(Val::Px(4.)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: BUTTON_COLOR.into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                label,
+                text_style(font, FONT_SIZE, Color::WHITE),
+            ));
+        });
+}
+
+pub fn spawn_main_menu(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn_bundle(screen_root())
+        .insert(MainMenuScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &font, "GoatLand");
+            spawn_button(parent, &font, "Play", MenuButton::Play);
+            spawn_button(parent, &font, "Quit", MenuButton::Quit);
+        });
+}
+
+pub fn spawn_world_select(mut commands: Commands, font: Res<UiFont>, mut form: ResMut<WorldForm>) {
+    *form = WorldForm::default();
+
+    commands
+        .spawn_bundle(screen_root())
+        .insert(WorldSelectScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &font, "Select world");
+
+            for name in list_worlds() {
+                spawn_button(parent, &font, &name, MenuButton::OpenWorld(name.clone()));
+            }
+
+            parent
+                .spawn_bundle(
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            form.describe(),
+                            text_style(&font, FONT_SIZE, Color::WHITE),
+                        ),
+                        TextSection::new("", text_style(&font, FONT_SIZE, ERROR_COLOR)),
+                    ])
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(16.)),
+                        ..default()
+                    }),
+                )
+                .insert(WorldFormText);
+
+            spawn_button(parent, &font, "Create new world", MenuButton::CreateWorld);
+            spawn_button(parent, &font, "Back", MenuButton::Back);
+        });
+}
+
+pub fn spawn_pause_menu(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn_bundle(screen_root())
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            spawn_title(parent, &font, "Paused");
+            spawn_button(parent, &font, "Resume", MenuButton::Resume);
+            spawn_button(parent, &font, "Save and quit", MenuButton::SaveAndQuit);
+        });
+}
+
+/// Loads a saved world and waits for its chunks, unless another screen was
+/// already picked this frame.
+fn play_world(
+    commands: &mut Commands,
+    registry: &BlockRegistry,
+    state: &mut State<GameState>,
+    name: &str,
+) {
+    if state.set(GameState::Loading).is_ok() {
+        open_world(commands, registry, name, LevelData::load(name));
+    }
+}
+
+/// Saves the world typed in the form and plays it, an empty seed picks one
+/// from `fallback_seed`.
+fn create_world(
+    commands: &mut Commands,
+    registry: &BlockRegistry,
+    state: &mut State<GameState>,
+    form: &mut WorldForm,
+    fallback_seed: u32,
+) {
+    let name = match world_name(&form.name) {
+        Some(name) => name,
+        None => {
+            form.error = Some("name the world first".to_string());
+            return;
+        }
+    };
+
+    if list_worlds().contains(&name) {
+        form.error = Some(format!("{} already exists", name));
+        return;
+    }
+
+    LevelData {
+        seed: parse_seed(&form.seed, fallback_seed),
+        ..default()
+    }
+    .save(&name);
+
+    play_world(commands, registry, state, &name);
+}
+
+/// Nanoseconds since startup, as good a seed as any when none was typed.
+fn time_seed(time: &Time) -> u32 {
+    time.time_since_startup().as_nanos() as u32
+}
+
+pub fn menu_buttons(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    mut form: ResMut<WorldForm>,
+    store: Res<VoxelStore>,
+    time: Res<Time>,
+    mut exit: EventWriter<AppExit>,
+    mut buttons: Query<(&Interaction, &MenuButton, &mut UiColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Clicked => {}
+            Interaction::Hovered => {
+                *color = HOVERED_COLOR.into();
+                continue;
+            }
+            Interaction::None => {
+                *color = BUTTON_COLOR.into();
+                continue;
+            }
+        }
+
+        // a click and a key can both switch screens in one frame, the first
+        // transition queued wins
+        match button {
+            MenuButton::Play => {
+                let _ = state.set(GameState::WorldSelect);
+            }
+            MenuButton::Quit => exit.send(AppExit),
+            MenuButton::OpenWorld(name) => {
+                play_world(&mut commands, &store.registry, &mut state, name)
+            }
+            MenuButton::CreateWorld => create_world(
+                &mut commands,
+                &store.registry,
+                &mut state,
+                &mut form,
+                time_seed(&time),
+            ),
+            MenuButton::Back => {
+                let _ = state.set(GameState::MainMenu);
+            }
+            MenuButton::Resume => {
+                let _ = state.pop();
+            }
+            // the world is saved and closed as the game leaves `Playing`
+            MenuButton::SaveAndQuit => {
+                let _ = state.replace(GameState::MainMenu);
+            }
+        }
+    }
+}
+
+/// Typing fills the focused field of the new world, Enter creates it and
+/// Escape goes back to the main menu.
+pub fn world_form_input(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    mut form: ResMut<WorldForm>,
+    store: Res<VoxelStore>,
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        let _ = state.set(GameState::MainMenu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        create_world(
+            &mut commands,
+            &store.registry,
+            &mut state,
+            &mut form,
+            time_seed(&time),
+        );
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Tab) {
+        form.field = match form.field {
+            FormField::Name => FormField::Seed,
+            FormField::Seed => FormField::Name,
+        };
+    }
+
+    let (field, max) = match form.field {
+        FormField::Name => (&mut form.name, MAX_NAME_LENGTH),
+        FormField::Seed => (&mut form.seed, MAX_SEED_LENGTH),
+    };
+
+    if keys.just_pressed(KeyCode::Back) {
+        field.pop();
+    }
+
+    for character in characters.iter() {
+        if !character.char.is_control() && field.chars().count() < max {
+            field.push(character.char);
+        }
+    }
+}
+
+pub fn update_world_form(form: Res<WorldForm>, mut texts: Query<&mut Text, With<WorldFormText>>) {
+    if !form.is_changed() {
+        return;
+    }
+
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = form.describe();
+        text.sections[1].value = form
+            .error
+            .as_ref()
+            .map_or(String::new(), |error| format!("\n{}", error));
+    }
+}
+
+/// Escape pauses and resumes the game, unless it closes the console. Runs
+/// before the console reads its keys.
+pub fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    console: Res<Console>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) || console.open {
+        return;
+    }
+
+    // ignored when the pause menu already queued a transition this frame
+    let _ = match state.current() {
+        GameState::Playing => state.push(GameState::Paused),
+        GameState::Paused => state.pop(),
+        _ => Ok(()),
+    };
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFont>()
+            .init_resource::<WorldForm>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(spawn_main_menu))
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(despawn_screen::<MainMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::WorldSelect).with_system(spawn_world_select),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::WorldSelect)
+                    .with_system(world_form_input)
+                    .with_system(update_world_form),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::WorldSelect)
+                    .with_system(despawn_screen::<WorldSelectScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(spawn_pause_menu))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused).with_system(despawn_screen::<PauseScreen>),
+            )
+            .add_system(menu_buttons)
+            .add_system(toggle_pause.before(console_input));
+    }
+}
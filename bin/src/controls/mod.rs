@@ -1 +1,8 @@
+pub mod camera;
+pub mod console;
+pub mod debug;
+pub mod hotbar;
+pub mod input;
+pub mod interact;
+pub mod menu;
 pub mod player;
@@ -1,82 +1,235 @@
-use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy::{prelude::*, time::FixedTimestep};
 
-use std::f32::consts::PI;
+use client::chunk::VoxelStore;
+use client::command::GameMode;
+use client::origin::{WorldOrigin, WorldPos};
+use client::physics::{sweep_step, Aabb};
+
+use super::camera::CameraLook;
+use super::input::{Action, StickInput};
+use crate::world::origin::{PlayerPosition, WorldAnchored};
+use crate::world::state::{while_playing, GameState};
 
 const SPEED: f32 = 20.;
-const RADIANT: f32 = PI / 180.;
 
+const PHYSICS_STEP: f32 = 1. / 60.;
+const WALK_SPEED: f32 = 4.3;
+const GRAVITY: f32 = 32.;
+const TERMINAL_VELOCITY: f32 = 60.;
+const JUMP_VELOCITY: f32 = 9.;
+const STEP_HEIGHT: f32 = 0.6;
+
+const PLAYER_HALF_WIDTH: f32 = 0.3;
+const PLAYER_HEIGHT: f32 = 1.8;
+pub const EYE_HEIGHT: f32 = 1.62;
+
+/// Player body, its transform sits at the feet and only carries the yaw.
 #[derive(Component)]
 pub struct PlayerController;
 
-#[allow(dead_code)]
-pub fn print_xyz(mut query: Query<(&Transform, With<PlayerController>)>) {
-    let (transform, _) = query.single_mut();
+/// Mesh shown for the body when the camera is not in first person.
+#[derive(Component)]
+pub struct PlayerModel;
+
+#[derive(Component, Default)]
+pub struct PlayerBody {
+    pub velocity: Vec3,
+    pub on_ground: bool,
+    wish: Vec3,
+    jump: bool,
+}
 
-    println!("{:?}", transform.translation);
+impl PlayerBody {
+    pub fn aabb(feet: Vec3) -> Aabb {
+        Aabb::from_feet(feet, PLAYER_HALF_WIDTH, PLAYER_HEIGHT)
+    }
 }
 
-pub fn camera_rotation(
-    mut mouse: EventReader<MouseMotion>,
-    time: Res<Time>,
-    mut query: Query<(&mut Transform, With<PlayerController>)>,
-) {
-    let (mut transform, _) = query.single_mut();
+/// Game mode of a player, set with `/gamemode`.
+#[derive(Component, Default)]
+pub struct PlayerMode(pub GameMode);
 
-    let mut delta = Vec2::ZERO;
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MovementMode {
+    Walking,
+    Spectator,
+}
 
-    for event in mouse.iter() {
-        delta += event.delta;
+impl Default for MovementMode {
+    fn default() -> Self {
+        Self::Walking
     }
+}
 
-    transform.rotate_local_x(delta.y * RADIANT * SPEED * time.delta_seconds() * -1.);
-    transform.rotate_y(delta.x * RADIANT * SPEED * time.delta_seconds() * -1.);
+pub fn spawn_player(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn_bundle(SpatialBundle {
+            transform: Transform::from_xyz(0., 30., 0.),
+            ..default()
+        })
+        .insert(PlayerController)
+        .insert(PlayerBody::default())
+        .insert(PlayerMode::default())
+        .insert(CameraLook::default())
+        .insert(PlayerPosition(WorldPos::new([0, 30, 0], Vec3::ZERO)))
+        .insert(WorldAnchored)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Capsule {
+                        radius: PLAYER_HALF_WIDTH,
+                        depth: PLAYER_HEIGHT - PLAYER_HALF_WIDTH * 2.,
+                        ..default()
+                    })),
+                    material: materials.add(Color::WHITE.into()),
+                    transform: Transform::from_xyz(0., PLAYER_HEIGHT / 2., 0.),
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                })
+                .insert(PlayerModel);
+        });
+}
+
+pub fn toggle_movement_mode(
+    actions: Res<Input<Action>>,
+    mut mode: ResMut<MovementMode>,
+    mut query: Query<&mut PlayerBody, With<PlayerController>>,
+) {
+    if !actions.just_pressed(Action::ToggleSpectator) {
+        return;
+    }
+
+    *mode = match *mode {
+        MovementMode::Walking => MovementMode::Spectator,
+        MovementMode::Spectator => MovementMode::Walking,
+    };
+
+    let mut body = query.single_mut();
+    body.velocity = Vec3::ZERO;
+    body.on_ground = false;
 }
 
 pub fn player_movement(
-    kbd: Res<Input<KeyCode>>,
-    mut query: Query<(&mut Transform, With<PlayerController>)>,
+    actions: Res<Input<Action>>,
+    sticks: Res<StickInput>,
+    mode: Res<MovementMode>,
+    mut query: Query<(
+        &Transform,
+        &mut PlayerPosition,
+        &mut PlayerBody,
+        With<PlayerController>,
+    )>,
     time: Res<Time>,
 ) {
-    let (mut transform, _) = query.single_mut();
+    let (transform, mut position, mut body, _) = query.single_mut();
 
-    let x: Vec3 = if kbd.pressed(KeyCode::Q) {
+    let x: Vec3 = if actions.pressed(Action::StrafeLeft) {
         let left = transform.left();
         Vec3::new(left.x, 0., left.z)
-    } else if kbd.pressed(KeyCode::D) {
+    } else if actions.pressed(Action::StrafeRight) {
         let right = transform.right();
         Vec3::new(right.x, 0., right.z)
     } else {
         Vec3::ZERO
     };
 
-    let y: Vec3 = if kbd.pressed(KeyCode::LShift) {
-        -Vec3::Y
-    } else if kbd.pressed(KeyCode::Space) {
-        Vec3::Y
-    } else {
-        Vec3::ZERO
-    };
-
-    let z: Vec3 = if kbd.pressed(KeyCode::Z) {
+    let z: Vec3 = if actions.pressed(Action::MoveForward) {
         let forward = transform.forward();
         Vec3::new(forward.x, 0., forward.z)
-    } else if kbd.pressed(KeyCode::S) {
+    } else if actions.pressed(Action::MoveBackward) {
         let back = transform.back();
         Vec3::new(back.x, 0., back.z)
     } else {
         Vec3::ZERO
     };
 
-    let translation = x + y + z;
+    let right = transform.right();
+    let forward = transform.forward();
+
+    let stick = Vec3::new(right.x, 0., right.z).normalize_or_zero() * sticks.movement.x
+        + Vec3::new(forward.x, 0., forward.z).normalize_or_zero() * sticks.movement.y;
 
-    transform.translation += translation * SPEED * time.delta_seconds();
+    if *mode == MovementMode::Walking {
+        body.wish = ((x + z).normalize_or_zero() + stick).clamp_length_max(1.) * WALK_SPEED;
+        body.jump = actions.pressed(Action::Jump);
+
+        return;
+    }
+
+    let y: Vec3 = if actions.pressed(Action::Descend) {
+        -Vec3::Y
+    } else if actions.pressed(Action::Jump) {
+        Vec3::Y
+    } else {
+        Vec3::ZERO
+    };
+
+    let translation = x + y + z + stick;
+
+    position.0 = position
+        .0
+        .translate(translation * SPEED * time.delta_seconds());
+}
+
+pub fn player_physics(
+    mode: Res<MovementMode>,
+    store: Res<VoxelStore>,
+    origin: Res<WorldOrigin>,
+    mut query: Query<(&mut PlayerPosition, &mut PlayerBody, With<PlayerController>)>,
+) {
+    if *mode != MovementMode::Walking {
+        return;
+    }
+
+    let (mut position, mut body, _) = query.single_mut();
+
+    body.velocity.x = body.wish.x;
+    body.velocity.z = body.wish.z;
+
+    if body.on_ground && body.jump {
+        body.velocity.y = JUMP_VELOCITY;
+    }
+
+    body.velocity.y = (body.velocity.y - GRAVITY * PHYSICS_STEP).max(-TERMINAL_VELOCITY);
+
+    let (offset, hit) = sweep_step(
+        &PlayerBody::aabb(origin.local(&position.0)),
+        body.velocity * PHYSICS_STEP,
+        STEP_HEIGHT,
+        body.on_ground,
+        |block| store.is_solid(origin.to_world(block)),
+    );
+
+    position.0 = position.0.translate(offset);
+
+    body.on_ground = hit.y && body.velocity.y < 0.;
+
+    if hit.y {
+        body.velocity.y = 0.;
+    }
 }
 
 pub struct PlayerControllerPlugin;
 
 impl Plugin for PlayerControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(player_movement).add_system(camera_rotation);
-        // .add_system(print_xyz);
+        app.init_resource::<MovementMode>()
+            .add_system_set(SystemSet::on_enter(GameState::Loading).with_system(spawn_player))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(toggle_movement_mode)
+                    .with_system(player_movement),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(
+                        FixedTimestep::step(PHYSICS_STEP as f64).chain(while_playing),
+                    )
+                    .with_system(player_physics),
+            );
     }
 }
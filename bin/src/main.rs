@@ -1,45 +1,1144 @@
-use bevy::{prelude::*, window::WindowMode};
+use bevy::prelude::*;
 
 mod controls;
+mod mobs;
 mod voxel;
+mod world;
 
 fn main() {
+    let (settings, problems) = world::settings::Settings::load();
+
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.5, 0.8, 0.9)))
-        .insert_resource(WindowDescriptor {
-            title: "GoatLand".to_string(),
-            width: 1920.,
-            height: 1080.,
-            resizable: false,
-            cursor_visible: false,
-            decorations: false,
-            canvas: Some("#canvas".to_string()),
-            fit_canvas_to_parent: true,
-            mode: WindowMode::BorderlessFullscreen,
-            ..default()
-        })
+        .insert_resource(settings.window_descriptor())
+        .insert_resource(settings)
+        .insert_resource(problems)
         .add_plugins(DefaultPlugins)
+        .add_plugin(world::settings::SettingsPlugin)
+        .add_plugin(world::state::GameStatePlugin)
+        .add_plugin(controls::menu::MenuPlugin)
+        .add_plugin(controls::input::InputMapPlugin)
         .add_plugin(controls::player::PlayerControllerPlugin)
+        .add_plugin(controls::camera::CameraControllerPlugin)
+        .add_plugin(controls::interact::BlockInteractionPlugin)
+        .add_plugin(controls::hotbar::HotbarPlugin)
+        .add_plugin(controls::console::ConsolePlugin)
+        .add_plugin(controls::debug::DebugOverlayPlugin)
         .add_plugin(voxel::terrain::TerrainGen)
-        .add_startup_system(setup)
+        .add_plugin(voxel::blocks::BlockAssetsPlugin)
+        .add_plugin(voxel::culling::CullingPlugin)
+        .add_plugin(world::save::SavePlugin)
+        .add_plugin(world::crafting::CraftingPlugin)
+        .add_plugin(mobs::MobsPlugin)
+        .add_plugin(world::daycycle::DayCyclePlugin)
+        .add_plugin(world::origin::FloatingOriginPlugin)
         .run();
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn_bundle(PointLightBundle {
-        point_light: PointLight {
-            intensity: 5000.,
-            shadows_enabled: true,
-            ..default()
-        },
-        transform: Transform::from_xyz(0., 20., 0.),
-        ..default()
-    });
-
-    commands
-        .spawn_bundle(Camera3dBundle {
-            transform: Transform::from_xyz(0., 10., 0.),
-            ..default()
-        })
-        .insert(controls::player::PlayerController);
+#[cfg(test)]
+mod goatland {
+    use bevy::{input::mouse::MouseMotion, prelude::*};
+
+    use client::chunk::{ChunkData, VoxelStore};
+    use client::origin::{WorldOrigin, WorldPos};
+
+    use crate::controls::{camera, console, hotbar, input, interact, menu, player};
+    use crate::mobs::{goat, pathfinding, MobBody};
+    use crate::voxel::terrain;
+    use crate::world::{origin, save, settings, state};
+
+    fn look_app() -> App {
+        let mut app = App::new();
+
+        app.add_event::<MouseMotion>()
+            .insert_resource(Time::default())
+            .insert_resource(input::InputMap::default())
+            .init_resource::<input::StickInput>()
+            .init_resource::<camera::CameraMode>()
+            .add_system(camera::camera_look);
+
+        app.world
+            .spawn()
+            .insert(Transform::default())
+            .insert(player::PlayerController)
+            .insert(camera::CameraLook::default());
+
+        app
+    }
+
+    fn move_mouse(app: &mut App, delta: Vec2) {
+        app.world
+            .resource_mut::<Events<MouseMotion>>()
+            .send(MouseMotion { delta });
+
+        app.update();
+    }
+
+    fn look(app: &mut App) -> (camera::CameraLook, Transform) {
+        let (look, transform) = app
+            .world
+            .query::<(&camera::CameraLook, &Transform)>()
+            .single(&app.world);
+
+        (*look, *transform)
+    }
+
+    #[test]
+    fn test_mouse_sensitivity() {
+        let mut app = look_app();
+        app.world
+            .resource_mut::<input::InputMap>()
+            .mouse
+            .sensitivity = 0.5;
+
+        move_mouse(&mut app, Vec2::new(-90., 0.));
+
+        let (look, _) = look(&mut app);
+        assert!((look.yaw - 45_f32.to_radians()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mouse_frame_independent() {
+        let mut once = look_app();
+        move_mouse(&mut once, Vec2::new(-40., 30.));
+
+        let mut split = look_app();
+        for _ in 0..4 {
+            move_mouse(&mut split, Vec2::new(-10., 7.5));
+        }
+
+        let (once, _) = look(&mut once);
+        let (split, _) = look(&mut split);
+
+        assert!((once.yaw - split.yaw).abs() < 1e-5);
+        assert!((once.pitch - split.pitch).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_pitch_clamp() {
+        let mut app = look_app();
+        move_mouse(&mut app, Vec2::new(0., -100_000.));
+
+        let (look, transform) = look(&mut app);
+
+        assert!(look.pitch < std::f32::consts::FRAC_PI_2);
+        assert!((look.rotation() * Vec3::Y).y > 0.);
+        assert!((look.rotation() * Vec3::NEG_Z).y > 0.99);
+        assert_eq!(transform.up(), Vec3::Y);
+    }
+
+    #[test]
+    fn test_invert_y() {
+        let mut app = look_app();
+        app.world.resource_mut::<input::InputMap>().mouse.invert_y = true;
+
+        move_mouse(&mut app, Vec2::new(0., 10.));
+
+        let (look, _) = look(&mut app);
+        assert!(look.pitch > 0.);
+    }
+
+    /// Stone floor at y = 9 over chunk (0, 0), with extra blocks on top.
+    fn goat_app(blocks: &[(IVec3, &str)]) -> App {
+        let mut store = VoxelStore::default();
+        let stone = store.registry.id("stone").unwrap();
+
+        let mut chunk = ChunkData::default();
+
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(IVec3::new(x, 9, z), stone);
+            }
+        }
+
+        for (position, name) in blocks {
+            chunk.set_block(*position, store.registry.id(name).unwrap());
+        }
+
+        store.insert(IVec2::ZERO, chunk);
+
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(store)
+            .init_resource::<WorldOrigin>()
+            .add_event::<pathfinding::PathRequest>()
+            .add_event::<pathfinding::PathResponse>()
+            .add_system(goat::goat_think);
+
+        app
+    }
+
+    /// Goat standing on the floor, walking toward +X for a while.
+    fn spawn_goat(app: &mut App, feet: Vec3) -> Entity {
+        let (marker, mut body, mut brain) = goat::goat_bundle(1);
+        body.on_ground = true;
+        brain.state = goat::GoatState::Wandering;
+        brain.heading = Vec3::X;
+        brain.timer = 100.;
+
+        app.world
+            .spawn()
+            .insert(Transform::from_translation(feet))
+            .insert_bundle((marker, body, brain))
+            .id()
+    }
+
+    fn goat_state(app: &App, goat: Entity) -> (&MobBody, &goat::GoatBrain) {
+        (
+            app.world.get::<MobBody>(goat).unwrap(),
+            app.world.get::<goat::GoatBrain>(goat).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_goat_wanders() {
+        let mut app = goat_app(&[]);
+        let goat = spawn_goat(&mut app, Vec3::new(4.5, 10., 8.5));
+
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(brain.state, goat::GoatState::Wandering);
+        assert!(body.wish.x > 0.);
+        assert_eq!(body.jump, None);
+    }
+
+    #[test]
+    fn test_goat_jumps_ledges() {
+        let mut app = goat_app(&[(IVec3::new(5, 10, 8), "stone")]);
+        let goat = spawn_goat(&mut app, Vec3::new(4.5, 10., 8.5));
+
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(body.jump, Some(goat::JUMP_VELOCITY));
+        assert_eq!(brain.heading, Vec3::X);
+        assert!(body.wish.x > 0.);
+
+        // two blocks up is a wall, the goat turns instead
+        let mut app = goat_app(&[
+            (IVec3::new(5, 10, 8), "stone"),
+            (IVec3::new(5, 11, 8), "stone"),
+        ]);
+        let goat = spawn_goat(&mut app, Vec3::new(4.5, 10., 8.5));
+
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(body.jump, None);
+        assert_eq!(brain.heading.x, 0.);
+        assert_eq!(body.wish, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_goat_avoids_water() {
+        for water in [IVec3::new(5, 10, 8), IVec3::new(5, 9, 8)] {
+            let mut app = goat_app(&[(water, "water")]);
+            let goat = spawn_goat(&mut app, Vec3::new(4.5, 10., 8.5));
+
+            app.update();
+
+            let (body, brain) = goat_state(&app, goat);
+            assert_eq!(brain.heading.x, 0.);
+            assert_eq!(body.wish, Vec3::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_goat_flees() {
+        let mut app = goat_app(&[]);
+        let goat = spawn_goat(&mut app, Vec3::new(8.5, 10., 8.5));
+
+        app.world
+            .spawn()
+            .insert(Transform::from_xyz(6.5, 10., 8.5))
+            .insert(player::PlayerController);
+
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(brain.state, goat::GoatState::Fleeing);
+        assert_eq!(brain.heading, Vec3::X);
+        assert!(body.wish.x > 0.);
+
+        // out of reach the goat keeps its calm
+        let mut app = goat_app(&[]);
+        let goat = spawn_goat(&mut app, Vec3::new(14.5, 10., 8.5));
+
+        app.world
+            .spawn()
+            .insert(Transform::from_xyz(2.5, 10., 8.5))
+            .insert(player::PlayerController);
+
+        app.update();
+
+        assert_eq!(goat_state(&app, goat).1.state, goat::GoatState::Wandering);
+    }
+
+    #[test]
+    fn test_mobs_out_of_world() {
+        let mut app = goat_app(&[]);
+        app.add_system(crate::mobs::mob_physics);
+
+        let above = spawn_goat(&mut app, Vec3::new(4.5, 140., 8.5));
+        let below = spawn_goat(&mut app, Vec3::new(4.5, -3., 8.5));
+
+        app.update();
+
+        // over the top the goat falls back, under the bottom it is gone
+        let transform = app.world.get::<Transform>(above).unwrap();
+        assert!(transform.translation.y < 140.);
+        assert!(app.world.get_entity(below).is_none());
+    }
+
+    #[test]
+    fn test_goat_follows_path() {
+        let mut app = goat_app(&[]);
+        let goat = spawn_goat(&mut app, Vec3::new(8.5, 10., 8.5));
+
+        // goats ask for a path when they set off
+        let mut reader = bevy::ecs::event::ManualEventReader::<pathfinding::PathRequest>::default();
+        let mut requests = Vec::new();
+
+        for _ in 0..20 {
+            app.world.get_mut::<goat::GoatBrain>(goat).unwrap().timer = 0.;
+            app.update();
+
+            let events = app.world.resource::<Events<pathfinding::PathRequest>>();
+            requests.extend(reader.iter(events).copied());
+
+            if !requests.is_empty() {
+                break;
+            }
+        }
+
+        assert_eq!(requests[0].entity, goat);
+        assert_eq!(requests[0].start, IVec3::new(8, 10, 8));
+        assert_eq!(requests[0].goal.y, 10);
+
+        let mut brain = app.world.get_mut::<goat::GoatBrain>(goat).unwrap();
+        brain.state = goat::GoatState::Wandering;
+        brain.timer = 100.;
+
+        app.world
+            .resource_mut::<Events<pathfinding::PathResponse>>()
+            .send(pathfinding::PathResponse {
+                entity: goat,
+                path: Some(vec![
+                    IVec3::new(8, 10, 8),
+                    IVec3::new(8, 10, 9),
+                    IVec3::new(7, 10, 9),
+                ]),
+            });
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(brain.path.len(), 2);
+        assert!(body.wish.z > 0. && body.wish.x.abs() < 1e-3);
+
+        // once in the next cell the goat turns toward the one after
+        app.world.get_mut::<Transform>(goat).unwrap().translation = Vec3::new(8.5, 10., 9.5);
+        app.update();
+        app.update();
+
+        let (body, brain) = goat_state(&app, goat);
+        assert_eq!(brain.path.len(), 1);
+        assert!(body.wish.x < 0. && body.wish.z.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_path_requests() {
+        let mut app = goat_app(&[(IVec3::new(8, 10, 8), "stone")]);
+        app.add_plugin(pathfinding::PathfindingPlugin);
+
+        let entity = app.world.spawn().id();
+        let agent = client::path::Agent::new(0.7, 0.9);
+
+        for goal in [
+            IVec3::new(12, 10, 8),
+            IVec3::new(12, 12, 8),
+            IVec3::new(500, 10, 8),
+        ] {
+            app.world
+                .resource_mut::<Events<pathfinding::PathRequest>>()
+                .send(pathfinding::PathRequest {
+                    entity,
+                    start: IVec3::new(4, 10, 8),
+                    goal,
+                    agent,
+                });
+        }
+
+        let mut reader =
+            bevy::ecs::event::ManualEventReader::<pathfinding::PathResponse>::default();
+        let mut responses = Vec::new();
+
+        // the search runs on another thread, give it a few frames
+        for _ in 0..1000 {
+            app.update();
+
+            let events = app.world.resource::<Events<pathfinding::PathResponse>>();
+            responses.extend(reader.iter(events).cloned());
+
+            if responses.len() == 3 {
+                break;
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|response| response.entity == entity));
+
+        // the stone in the way is climbed over, the floating and the far goals
+        // unreachable
+        let paths: Vec<_> = responses
+            .iter()
+            .filter_map(|response| response.path.as_ref())
+            .collect();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].contains(&IVec3::new(8, 11, 8)));
+    }
+
+    #[test]
+    fn test_console_commands() {
+        let mut app = App::new();
+
+        app.insert_resource(VoxelStore::default())
+            .init_resource::<client::time::WorldTime>()
+            .init_resource::<client::inventory::Inventory>()
+            .init_resource::<player::MovementMode>()
+            .init_resource::<console::Console>()
+            .init_resource::<settings::Settings>()
+            .insert_resource(save::ActiveWorld {
+                name: "test".to_string(),
+                seed: 7,
+            })
+            .add_event::<input::StartRebind>()
+            .add_system(console::run_commands);
+
+        let player = app
+            .world
+            .spawn()
+            .insert(origin::PlayerPosition(WorldPos::new(
+                [4, 20, 4],
+                Vec3::new(0.5, 0., 0.5),
+            )))
+            .insert(player::PlayerBody::default())
+            .insert(player::PlayerMode::default())
+            .insert(player::PlayerController)
+            .id();
+
+        let mut console = app.world.resource_mut::<console::Console>();
+
+        for line in [
+            "/give torch 5",
+            "/give gold",
+            "/time set noon",
+            "/tp 10 ~2 ~-0.5",
+            "/gamemode spectator",
+            "/seed",
+            "/bind jump",
+            "/bind fly",
+            "/set view_distance 8",
+            "/set fov 200",
+            "/set vsync maybe",
+            "/set colour blue",
+        ] {
+            assert_eq!(console.submit(line), None);
+        }
+
+        // chat and misspelled commands never wait for an update
+        assert_eq!(console.submit("hello"), Some("hello".to_string()));
+        assert_eq!(console.submit("/tp 1"), None);
+
+        app.update();
+
+        let store = app.world.resource::<VoxelStore>();
+        let torch = store.registry.id("torch").unwrap();
+        let inventory = app.world.resource::<client::inventory::Inventory>();
+        assert_eq!(
+            inventory.get(0),
+            Some(client::inventory::ItemStack::new(torch, 5))
+        );
+
+        assert_eq!(
+            app.world.resource::<client::time::WorldTime>().time_of_day,
+            0.5
+        );
+
+        let position = app.world.get::<origin::PlayerPosition>(player).unwrap();
+        assert_eq!(position.0, WorldPos::new([10, 22, 4], Vec3::ZERO));
+
+        assert_eq!(
+            *app.world.resource::<player::MovementMode>(),
+            player::MovementMode::Spectator
+        );
+        assert_eq!(
+            app.world.get::<player::PlayerMode>(player).unwrap().0,
+            client::command::GameMode::Spectator
+        );
+
+        let log: Vec<_> = app
+            .world
+            .resource::<console::Console>()
+            .lines()
+            .cloned()
+            .collect();
+        assert!(log.contains(&"unknown block gold".to_string()));
+        assert!(log.contains(&"<player> hello".to_string()));
+        assert!(log.contains(&"seed: 7".to_string()));
+        assert!(log.contains(&"usage: /tp <x> <y> <z>".to_string()));
+        assert!(log.contains(&"unknown action fly".to_string()));
+        assert!(log.contains(&"view_distance set to 8".to_string()));
+        assert!(log.contains(&"fov 200 out of 30..=110".to_string()));
+        assert!(log.contains(&"invalid value maybe for vsync".to_string()));
+        assert!(log.contains(&"unknown setting colour".to_string()));
+
+        let video = &app.world.resource::<settings::Settings>().video;
+        assert_eq!(video.view_distance, 8);
+        assert_eq!(video.fov, 45.);
+
+        let rebinds = app.world.resource::<Events<input::StartRebind>>();
+        let actions: Vec<_> = rebinds
+            .get_reader()
+            .iter(rebinds)
+            .map(|rebind| rebind.0)
+            .collect();
+        assert_eq!(actions, [input::Action::Jump]);
+    }
+
+    #[test]
+    fn test_console_offline() {
+        let mut app = App::new();
+
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<console::Console>()
+            .add_event::<ReceivedCharacter>()
+            .add_system(console::console_input);
+
+        let mut console = app.world.resource_mut::<console::Console>();
+        console.open = true;
+        console.input = "hello".to_string();
+
+        press_key(&mut app, KeyCode::Return);
+
+        let console = app.world.resource::<console::Console>();
+        assert!(!console.open);
+
+        let log: Vec<_> = console.lines().cloned().collect();
+        assert_eq!(log, ["<player> hello", "not connected to a server"]);
+    }
+
+    #[test]
+    fn test_rebind() {
+        let mut app = App::new();
+
+        app.init_resource::<settings::Settings>()
+            .init_resource::<input::InputMap>()
+            .init_resource::<input::PendingRebind>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Input<GamepadButton>>()
+            .add_event::<input::StartRebind>()
+            .add_system(input::capture_rebind);
+
+        app.world
+            .resource_mut::<Events<input::StartRebind>>()
+            .send(input::StartRebind(input::Action::Jump));
+        app.update();
+
+        // the next key pressed is the new binding
+        press_key(&mut app, KeyCode::J);
+
+        let map = app.world.resource::<input::InputMap>();
+        assert_eq!(
+            map.bindings(input::Action::Jump),
+            [input::Binding::Key(KeyCode::J)]
+        );
+
+        // and is saved with the settings
+        let config = &app.world.resource::<settings::Settings>().controls;
+        assert_eq!(
+            config.bindings.get(&input::Action::Jump),
+            Some(&vec![input::Binding::Key(KeyCode::J)])
+        );
+
+        // other keys keep their action
+        press_key(&mut app, KeyCode::K);
+        let map = app.world.resource::<input::InputMap>();
+        assert_eq!(
+            map.bindings(input::Action::Jump),
+            [input::Binding::Key(KeyCode::J)]
+        );
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut config: settings::Settings = ron::from_str(
+            "(video: (mode: Windowed, fov: 200., view_distance: 0), controls: (layout: Qwerty))",
+        )
+        .unwrap();
+
+        // missing values keep their defaults
+        assert_eq!(config.video.width, 1920.);
+        assert!(config.video.vsync);
+
+        let problems = config.validate();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(config.video.fov, 110.);
+        assert_eq!(config.video.view_distance, 2);
+        assert!(config.validate().is_empty());
+
+        config.controls.mouse.sensitivity = f32::NAN;
+        assert_eq!(config.validate().len(), 1);
+        assert_eq!(config.controls.mouse.sensitivity, 0.01);
+
+        let window = config.window_descriptor();
+        assert_eq!(window.mode, bevy::window::WindowMode::Windowed);
+        assert!(window.resizable && window.decorations);
+
+        let map = input::InputMap::from_config(config.controls.clone());
+        assert_eq!(
+            map.bindings(input::Action::MoveForward),
+            [input::Binding::Key(KeyCode::W)]
+        );
+
+        // saved settings read back the same
+        let text = ron::to_string(&config).unwrap();
+        let read: settings::Settings = ron::from_str(&text).unwrap();
+        assert_eq!(read.video, config.video);
+        assert_eq!(read.controls.layout, input::KeyboardLayout::Qwerty);
+    }
+
+    #[test]
+    fn test_settings_clamped() {
+        let mut config = settings::Settings::default();
+        config.video.view_distance = 0;
+
+        let mut app = App::new();
+
+        app.insert_resource(config)
+            .init_resource::<Windows>()
+            .init_resource::<input::InputMap>()
+            .add_system(settings::apply_settings);
+
+        app.update();
+
+        // systems reading the resource see it clamped
+        let config = app.world.resource::<settings::Settings>();
+        assert_eq!(config.video.view_distance, 2);
+    }
+
+    #[test]
+    fn test_hotbar_actions() {
+        use client::inventory::Inventory;
+
+        let mut app = App::new();
+
+        app.add_event::<bevy::input::mouse::MouseWheel>()
+            .init_resource::<Input<input::Action>>()
+            .init_resource::<console::Console>()
+            .init_resource::<Inventory>()
+            .add_system(hotbar::select_slot);
+
+        // the number keys select slots unless rebound
+        let map = input::InputMap::default();
+        assert_eq!(
+            map.bindings(input::Action::HotbarSlot3),
+            [input::Binding::Key(KeyCode::Key3)]
+        );
+
+        app.world
+            .resource_mut::<Input<input::Action>>()
+            .press(input::Action::HotbarSlot3);
+        app.update();
+
+        assert_eq!(app.world.resource::<Inventory>().selected(), 2);
+    }
+
+    #[test]
+    fn test_save_level() {
+        let name = "save level test";
+
+        let mut app = App::new();
+
+        app.add_state(state::GameState::Playing)
+            .add_event::<bevy::app::AppExit>()
+            .init_resource::<Time>()
+            .add_plugin(save::SavePlugin);
+
+        // nothing to save before a world is opened
+        app.world
+            .resource_mut::<Events<bevy::app::AppExit>>()
+            .send(bevy::app::AppExit);
+        app.update();
+        assert!(!save::list_worlds().contains(&name.to_string()));
+
+        app.insert_resource(save::ActiveWorld {
+            name: name.to_string(),
+            seed: 42,
+        });
+        app.world
+            .resource_mut::<Events<bevy::app::AppExit>>()
+            .send(bevy::app::AppExit);
+        app.update();
+
+        let saved = save::list_worlds().contains(&name.to_string());
+        let level = save::LevelData::load(name);
+        std::fs::remove_dir_all(std::path::Path::new("saves").join(name)).unwrap();
+
+        assert!(saved);
+        assert_eq!(level.seed, 42);
+        assert!(level.inventory.is_some());
+    }
+
+    #[test]
+    fn test_leave_world() {
+        let name = "leave world test";
+
+        let mut store = VoxelStore::default();
+        store.insert(IVec2::ZERO, ChunkData::default());
+
+        let mut app = App::new();
+
+        app.add_state(state::GameState::Playing)
+            .add_event::<bevy::app::AppExit>()
+            .insert_resource(store)
+            .init_resource::<terrain::ChunkEntities>()
+            .init_resource::<WorldOrigin>()
+            .init_resource::<player::MovementMode>()
+            .init_resource::<menu::WorldForm>()
+            .init_resource::<Time>()
+            .insert_resource(save::ActiveWorld {
+                name: name.to_string(),
+                seed: 3,
+            })
+            .add_plugin(save::SavePlugin)
+            .add_system(menu::menu_buttons);
+
+        let chunk = app
+            .world
+            .spawn()
+            .insert(terrain::Chunk {
+                position: IVec2::ZERO,
+                lod: 1,
+                sections: Vec::new(),
+            })
+            .id();
+        app.world
+            .resource_mut::<terrain::ChunkEntities>()
+            .0
+            .insert(IVec2::ZERO, chunk);
+
+        let (goat, body, brain) = goat::goat_bundle(1);
+        let goat = app.world.spawn().insert_bundle((goat, body, brain)).id();
+        let player = app.world.spawn().insert(player::PlayerController).id();
+
+        app.world
+            .resource_mut::<State<state::GameState>>()
+            .push(state::GameState::Paused)
+            .unwrap();
+        app.update();
+
+        app.world
+            .spawn()
+            .insert_bundle((Interaction::Clicked, menu::MenuButton::SaveAndQuit))
+            .insert(UiColor::default());
+        app.update();
+
+        let saved = save::LevelData::load(name);
+        std::fs::remove_dir_all(std::path::Path::new("saves").join(name)).unwrap();
+        assert_eq!(saved.seed, 3);
+
+        // back on the main menu with nothing left of the world
+        assert_eq!(game_state(&app), state::GameState::MainMenu);
+
+        for entity in [chunk, goat, player] {
+            assert!(app.world.get_entity(entity).is_none());
+        }
+
+        assert!(app.world.resource::<VoxelStore>().is_empty());
+        assert!(app.world.resource::<terrain::ChunkEntities>().0.is_empty());
+        assert!(app.world.get_resource::<save::ActiveWorld>().is_none());
+    }
+
+    #[test]
+    fn test_world_names() {
+        assert_eq!(
+            save::world_name("  My World/../ "),
+            Some("My World".to_string())
+        );
+        assert_eq!(save::world_name("../.."), None);
+        assert_eq!(
+            save::world_name(&"a".repeat(100)).unwrap().len(),
+            save::MAX_NAME_LENGTH
+        );
+
+        assert_eq!(save::parse_seed(" 42 ", 7), 42);
+        assert_eq!(save::parse_seed("", 7), 7);
+        assert_eq!(save::parse_seed("goat", 7), save::parse_seed("goat", 8));
+        assert_ne!(save::parse_seed("goat", 7), save::parse_seed("goats", 7));
+
+        // worlds saved before seeds keep their terrain
+        let level: save::LevelData = ron::from_str("(inventory: None)").unwrap();
+        assert_eq!(level.seed, terrain::SEED);
+    }
+
+    fn press_key(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+        app.update();
+    }
+
+    fn game_state(app: &App) -> state::GameState {
+        *app.world.resource::<State<state::GameState>>().current()
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut app = App::new();
+
+        app.add_state(state::GameState::Playing)
+            .add_event::<bevy::app::AppExit>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<console::Console>()
+            .init_resource::<menu::WorldForm>()
+            .init_resource::<VoxelStore>()
+            .init_resource::<Time>()
+            .add_system(menu::menu_buttons)
+            .add_system(menu::toggle_pause);
+
+        press_key(&mut app, KeyCode::Escape);
+        assert_eq!(game_state(&app), state::GameState::Paused);
+
+        press_key(&mut app, KeyCode::Escape);
+        assert_eq!(game_state(&app), state::GameState::Playing);
+
+        // resuming with a click and a key in the same frame resumes once
+        press_key(&mut app, KeyCode::Escape);
+        app.world
+            .spawn()
+            .insert_bundle((Interaction::Clicked, menu::MenuButton::Resume))
+            .insert(UiColor::default());
+        press_key(&mut app, KeyCode::Escape);
+        assert_eq!(game_state(&app), state::GameState::Playing);
+
+        // escape only closes the console
+        app.world.resource_mut::<console::Console>().open = true;
+        press_key(&mut app, KeyCode::Escape);
+        assert_eq!(game_state(&app), state::GameState::Playing);
+    }
+
+    #[test]
+    fn test_loading() {
+        let mut store = VoxelStore::default();
+        let stone = store.registry.id("stone").unwrap();
+
+        let mut app = App::new();
+        let mut chunks = terrain::ChunkEntities::default();
+
+        for x in -2..=2 {
+            for z in -2..=2 {
+                let mut chunk = ChunkData::default();
+
+                for y in 0..12 {
+                    chunk.set_block(IVec3::new(0, y, 0), stone);
+                }
+
+                store.insert(IVec2::new(x, z), chunk);
+                chunks.0.insert(IVec2::new(x, z), app.world.spawn().id());
+            }
+        }
+
+        let pending = chunks.0[&IVec2::new(2, -1)];
+        app.world.entity_mut(pending).insert(terrain::NeedsMesh);
+
+        app.add_state(state::GameState::Loading)
+            .insert_resource(store)
+            .insert_resource(chunks)
+            .add_system_set(
+                SystemSet::on_update(state::GameState::Loading).with_system(state::wait_for_chunks),
+            );
+
+        let player = app
+            .world
+            .spawn()
+            .insert(origin::PlayerPosition(WorldPos::new(
+                [0, 30, 0],
+                Vec3::new(0.5, 0.2, 0.5),
+            )))
+            .insert(player::PlayerController)
+            .id();
+
+        app.update();
+        assert_eq!(game_state(&app), state::GameState::Loading);
+
+        app.world.entity_mut(pending).remove::<terrain::NeedsMesh>();
+        app.update();
+        app.update();
+        assert_eq!(game_state(&app), state::GameState::Playing);
+
+        // set down on the column at the spawn
+        let position = app.world.get::<origin::PlayerPosition>(player).unwrap();
+        assert_eq!(
+            position.0,
+            WorldPos::new([0, 12, 0], Vec3::new(0.5, 0., 0.5))
+        );
+    }
+
+    #[test]
+    fn test_chunk_eviction() {
+        let mut config = settings::Settings::default();
+        config.video.view_distance = 2;
+
+        let mut app = App::new();
+
+        app.insert_resource(VoxelStore::default())
+            .insert_resource(config)
+            .add_system(terrain::evict_chunks);
+
+        let player = app
+            .world
+            .spawn()
+            .insert(origin::PlayerPosition::default())
+            .id();
+
+        // the streamed ring plus the margin of neighbours, and the edited chunk
+        let bound = 7 * 7 + 1;
+
+        for step in 0..20 {
+            let center = IVec2::new(step, 0);
+
+            for x in -3..=3 {
+                for z in -3..=3 {
+                    let coord = center + IVec2::new(x, z);
+                    let mut store = app.world.resource_mut::<VoxelStore>();
+
+                    if !store.is_loaded(coord) {
+                        store.insert(coord, ChunkData::default());
+                    }
+                }
+            }
+
+            // neighbours of the next ring are generated ahead
+            let ahead = center + IVec2::new(4, 0);
+            app.world
+                .resource_mut::<VoxelStore>()
+                .insert(ahead, ChunkData::default());
+
+            app.world
+                .get_mut::<origin::PlayerPosition>(player)
+                .unwrap()
+                .0 = client::origin::WorldPos::new([step as i64 * 16, 0, 0], Vec3::ZERO);
+            if step == 0 {
+                app.world
+                    .resource_mut::<VoxelStore>()
+                    .replace(IVec3::new(1, 5, 1), client::block::BlockId(1));
+            }
+
+            app.update();
+
+            let store = app.world.resource::<VoxelStore>();
+            assert!(store.len() <= bound, "{} chunks loaded", store.len());
+            assert!(store.is_loaded(IVec2::ZERO));
+            assert!(store.is_loaded(center + IVec2::new(-3, 3)));
+            assert!(!store.is_loaded(ahead));
+        }
+    }
+
+    #[test]
+    fn test_player_position() {
+        // a million blocks out, where f32 positions are off by a sixteenth
+        let far = 1_000_000;
+
+        let mut store = VoxelStore::default();
+        let stone = store.registry.id("stone").unwrap();
+        let mut chunk = ChunkData::default();
+
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(IVec3::new(x, 9, z), stone);
+            }
+        }
+
+        store.insert(IVec2::new(far / 16, 0), chunk);
+
+        let mut app = App::new();
+
+        app.insert_resource(store)
+            .insert_resource(WorldOrigin([far as i64, 0, 0]))
+            .init_resource::<player::MovementMode>()
+            .add_system(player::player_physics)
+            .add_system_to_stage(CoreStage::PostUpdate, origin::place_players);
+
+        let player = app
+            .world
+            .spawn()
+            .insert(Transform::default())
+            .insert(origin::PlayerPosition(WorldPos::new(
+                [far as i64 + 4, 12, 4],
+                Vec3::new(0.3, 0., 0.3),
+            )))
+            .insert(player::PlayerBody::default())
+            .insert(player::PlayerController)
+            .id();
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        let position = app.world.get::<origin::PlayerPosition>(player).unwrap().0;
+        assert_eq!(position.block[0], far as i64 + 4);
+        assert_eq!(position.offset.x, 0.3);
+        assert_eq!(position.offset.z, 0.3);
+        assert!(
+            app.world
+                .get::<player::PlayerBody>(player)
+                .unwrap()
+                .on_ground
+        );
+
+        // the transform follows, relative to the origin
+        let transform = app.world.get::<Transform>(player).unwrap();
+        assert!((transform.translation - Vec3::new(4.3, 10., 4.3)).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_rebase_anchored() {
+        let mut app = App::new();
+
+        app.init_resource::<WorldOrigin>()
+            .add_system(origin::rebase_origin);
+
+        app.world
+            .spawn()
+            .insert(origin::PlayerPosition(WorldPos::new(
+                [1000, 10, 0],
+                Vec3::ZERO,
+            )));
+
+        let goat = app
+            .world
+            .spawn()
+            .insert(Transform::from_xyz(1000., 10., 0.))
+            .insert(origin::WorldAnchored)
+            .id();
+
+        // stands in for the camera, lights and UI roots
+        let camera = app
+            .world
+            .spawn()
+            .insert(Transform::from_xyz(1000., 10., 0.))
+            .id();
+
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<WorldOrigin>(),
+            WorldOrigin([992, 0, 0])
+        );
+
+        let moved = app.world.get::<Transform>(goat).unwrap().translation;
+        assert_eq!(moved, Vec3::new(8., 10., 0.));
+
+        let kept = app.world.get::<Transform>(camera).unwrap().translation;
+        assert_eq!(kept, Vec3::new(1000., 10., 0.));
+    }
+
+    #[test]
+    fn test_asset_reload() {
+        use crate::voxel::blocks::{asset_path, AssetsSnapshot};
+
+        // found from the manifest whatever the working directory
+        assert!(asset_path("assets/blocks").join("grass.ron").is_file());
+
+        let dir = std::env::temp_dir().join("goatland asset reload test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.ron"), "()").unwrap();
+        std::fs::write(dir.join("b.ron"), "()").unwrap();
+
+        let dirs = [dir.clone()];
+        let before = AssetsSnapshot::take(&dirs);
+        assert_eq!(AssetsSnapshot::take(&dirs), before);
+
+        // deleting a file can leave the latest time as it was
+        std::fs::remove_file(dir.join("b.ron")).unwrap();
+        assert_ne!(AssetsSnapshot::take(&dirs), before);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_break_full_inventory() {
+        use client::block::BlockId;
+        use client::command::GameMode;
+        use client::inventory::{Inventory, ItemStack};
+
+        // one block under the floor, the ray reaches it once the floor is gone
+        let mut app = goat_app(&[(IVec3::new(8, 8, 8), "stone")]);
+
+        let store = app.world.resource::<VoxelStore>();
+        let stone = store.registry.id("stone").unwrap();
+        let torch = store.registry.id("torch").unwrap();
+
+        // a single slot, full of torches
+        let mut inventory = Inventory::new(1);
+        inventory.insert(ItemStack::new(torch, u8::MAX), &store.registry);
+
+        app.insert_resource(inventory)
+            .init_resource::<Input<input::Action>>()
+            .init_resource::<terrain::ChunkEntities>()
+            .add_system(interact::break_and_place);
+
+        let player = app
+            .world
+            .spawn()
+            .insert(Transform::from_xyz(8.5, 10., 8.5))
+            .insert(camera::CameraLook {
+                yaw: 0.,
+                pitch: -std::f32::consts::FRAC_PI_2,
+            })
+            .insert(player::PlayerMode::default())
+            .insert(player::PlayerController)
+            .id();
+
+        let break_block = |app: &mut App, position: IVec3| {
+            let mut actions = app.world.resource_mut::<Input<input::Action>>();
+            actions.reset(input::Action::Break);
+            actions.press(input::Action::Break);
+
+            app.update();
+
+            app.world.resource::<VoxelStore>().block(position)
+        };
+
+        let floor = IVec3::new(8, 9, 8);
+
+        // nowhere to put it, the block stays
+        assert_eq!(break_block(&mut app, floor), Some(stone));
+        assert_eq!(
+            app.world.resource::<Inventory>().get(0).unwrap().block,
+            torch
+        );
+
+        // creative players break it anyway
+        app.world.get_mut::<player::PlayerMode>(player).unwrap().0 = GameMode::Creative;
+        assert_eq!(break_block(&mut app, floor), Some(BlockId::AIR));
+
+        // with room, the block below goes to the inventory
+        app.world.get_mut::<player::PlayerMode>(player).unwrap().0 = GameMode::Survival;
+        *app.world.resource_mut::<Inventory>() = Inventory::new(1);
+
+        assert_eq!(break_block(&mut app, floor - IVec3::Y), Some(BlockId::AIR));
+        assert_eq!(
+            app.world.resource::<Inventory>().get(0),
+            Some(ItemStack::new(stone, 1))
+        );
+    }
 }
@@ -0,0 +1,434 @@
+use bevy::prelude::*;
+
+use client::biome::Biome;
+use client::block::BlockId;
+use client::chunk::{VoxelStore, CHUNK_HEIGHT};
+use client::coords::BlockPos;
+use client::origin::WorldOrigin;
+use client::path::Agent;
+
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+use super::pathfinding::{PathRequest, PathResponse};
+use super::{MobBody, Rng};
+use crate::controls::player::PlayerController;
+use crate::world::origin::{PlayerPosition, WorldAnchored};
+use crate::world::state::GameState;
+
+const GOAT_HALF_WIDTH: f32 = 0.35;
+const GOAT_HEIGHT: f32 = 0.9;
+
+const WANDER_SPEED: f32 = 1.5;
+const FLEE_SPEED: f32 = 5.;
+/// Clears a one block ledge but not two.
+pub const JUMP_VELOCITY: f32 = 9.;
+
+/// Distance to the player under which goats run away.
+const FLEE_RADIUS: f32 = 6.;
+/// Seconds a goat keeps running once the player is out of reach.
+const FLEE_TIME: f32 = 3.;
+/// Distance past its body at which a goat looks at the ground ahead.
+const LOOKAHEAD: f32 = 0.5;
+/// Blocks ahead a wandering goat asks for a path to.
+const WANDER_DISTANCE: f32 = 8.;
+/// Horizontal distance to the center of a path cell at which it is reached.
+const WAYPOINT_RADIUS: f32 = 0.3;
+
+const MAX_GOATS: usize = 12;
+const SPAWN_INTERVAL: f32 = 5.;
+/// Columns tried around the player each spawn round.
+const SPAWN_ATTEMPTS: usize = 8;
+const SPAWN_MIN_DISTANCE: f32 = 24.;
+const SPAWN_MAX_DISTANCE: f32 = 64.;
+const DESPAWN_DISTANCE: f32 = 128.;
+
+#[derive(Component)]
+pub struct Goat;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GoatState {
+    Idle,
+    Wandering,
+    Fleeing,
+}
+
+#[derive(Component)]
+pub struct GoatBrain {
+    pub state: GoatState,
+    /// Horizontal unit direction the goat walks toward.
+    pub heading: Vec3,
+    /// Seconds left before the goat picks something else to do.
+    pub timer: f32,
+    /// Cells left to walk through while wandering, in world blocks.
+    pub path: VecDeque<IVec3>,
+    rng: Rng,
+}
+
+impl GoatBrain {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: GoatState::Idle,
+            heading: Vec3::Z,
+            timer: 0.,
+            path: VecDeque::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Stands still or sets off in a random direction for a few seconds.
+    fn pick(&mut self) {
+        self.path.clear();
+
+        if self.rng.next() % 3 == 0 {
+            self.state = GoatState::Idle;
+        } else {
+            let angle = self.rng.unit() * TAU;
+
+            self.state = GoatState::Wandering;
+            self.heading = Vec3::new(angle.sin(), 0., angle.cos());
+        }
+
+        self.timer = 2. + self.rng.unit() * 4.;
+    }
+
+    /// Quarter turn to either side.
+    fn turn(&mut self) {
+        let side = if self.rng.next() % 2 == 0 { 1. } else { -1. };
+
+        self.heading = Vec3::new(self.heading.z * side, 0., -self.heading.x * side);
+    }
+}
+
+pub fn goat_bundle(seed: u32) -> (Goat, MobBody, GoatBrain) {
+    (
+        Goat,
+        MobBody::new(GOAT_HALF_WIDTH, GOAT_HEIGHT),
+        GoatBrain::new(seed),
+    )
+}
+
+fn goat_agent() -> Agent {
+    Agent::new(GOAT_HALF_WIDTH * 2., GOAT_HEIGHT)
+}
+
+/// Cell the feet are in, they rest on the top of the block below.
+fn feet_cell(feet: Vec3) -> IVec3 {
+    BlockPos::from_world(feet + Vec3::Y * 0.01).0
+}
+
+/// Ground a wandering goat heads for, a few blocks along its heading.
+fn wander_goal(
+    store: &VoxelStore,
+    origin: &WorldOrigin,
+    feet: Vec3,
+    heading: Vec3,
+) -> Option<IVec3> {
+    let column = origin.to_world(feet_cell(feet + heading * WANDER_DISTANCE));
+
+    store
+        .surface(column.xz())
+        .map(|ground| IVec3::new(column.x, ground + 1, column.z))
+}
+
+/// What a goat would walk into next.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Ahead {
+    Clear,
+    /// One block up, with room above to jump onto.
+    Ledge,
+    Wall,
+    Water,
+}
+
+fn ahead(store: &VoxelStore, origin: &WorldOrigin, feet: Vec3, heading: Vec3, reach: f32) -> Ahead {
+    let cell = feet_cell(feet + heading * reach);
+    let block = |height: i32| store.block(origin.to_world(cell + IVec3::Y * height));
+    let liquid =
+        |height: i32| block(height).map_or(false, |block| store.registry.get(block).liquid);
+    let solid = |height: i32| store.is_solid(origin.to_world(cell + IVec3::Y * height));
+
+    if block(0).is_none() {
+        // unloaded chunks are walls until generated
+        Ahead::Wall
+    } else if liquid(0) || liquid(-1) {
+        Ahead::Water
+    } else if !solid(0) {
+        Ahead::Clear
+    } else if solid(1) {
+        Ahead::Wall
+    } else {
+        Ahead::Ledge
+    }
+}
+
+/// Picks what each goat does: running from a nearby player, wandering or
+/// standing. Wandering goats ask for a path and follow it once found, until
+/// then they jump ledges or turn away from walls and water on the way.
+pub fn goat_think(
+    time: Res<Time>,
+    store: Res<VoxelStore>,
+    origin: Res<WorldOrigin>,
+    mut requests: EventWriter<PathRequest>,
+    mut responses: EventReader<PathResponse>,
+    players: Query<&Transform, (With<PlayerController>, Without<Goat>)>,
+    mut goats: Query<(Entity, &mut Transform, &mut MobBody, &mut GoatBrain), With<Goat>>,
+) {
+    for response in responses.iter() {
+        let mut brain = match goats.get_mut(response.entity) {
+            Ok((_, _, _, brain)) => brain,
+            Err(_) => continue,
+        };
+
+        // the goat may have been scared off since it asked
+        if let (GoatState::Wandering, Some(path)) = (brain.state, &response.path) {
+            // the first cell is the one the goat stood in
+            brain.path = path.iter().skip(1).copied().collect();
+        }
+    }
+
+    for (entity, mut transform, mut body, mut brain) in goats.iter_mut() {
+        let feet = transform.translation;
+
+        let threat = players
+            .iter()
+            .map(|player| player.translation)
+            .filter(|player| player.distance(feet) < FLEE_RADIUS)
+            .min_by(|a, b| a.distance(feet).total_cmp(&b.distance(feet)));
+
+        brain.timer -= time.delta_seconds();
+
+        if let Some(player) = threat {
+            let away = Vec3::new(feet.x - player.x, 0., feet.z - player.z).normalize_or_zero();
+
+            if away != Vec3::ZERO {
+                brain.heading = away;
+            }
+
+            brain.state = GoatState::Fleeing;
+            brain.timer = FLEE_TIME;
+            brain.path.clear();
+        } else if brain.timer <= 0. {
+            brain.pick();
+
+            if brain.state == GoatState::Wandering {
+                if let Some(goal) = wander_goal(&store, &origin, feet, brain.heading) {
+                    requests.send(PathRequest {
+                        entity,
+                        start: origin.to_world(feet_cell(feet)),
+                        goal,
+                        agent: goat_agent(),
+                    });
+                }
+            }
+        }
+
+        let speed = match brain.state {
+            GoatState::Idle => 0.,
+            GoatState::Wandering => WANDER_SPEED,
+            GoatState::Fleeing => FLEE_SPEED,
+        };
+
+        body.wish = Vec3::ZERO;
+
+        if speed == 0. {
+            continue;
+        }
+
+        if let Some(next) = brain.path.front().copied() {
+            let target = origin.to_local(next).as_vec3() + Vec3::new(0.5, 0., 0.5);
+            let offset = Vec3::new(target.x - feet.x, 0., target.z - feet.z);
+
+            if offset.length() < WAYPOINT_RADIUS {
+                brain.path.pop_front();
+
+                // picks something else to do at the end of the path
+                if brain.path.is_empty() {
+                    brain.timer = 0.;
+                }
+
+                continue;
+            }
+
+            if target.y > feet.y + 0.5 && body.on_ground {
+                body.jump = Some(JUMP_VELOCITY);
+            }
+
+            brain.heading = offset.normalize();
+            body.wish = brain.heading * speed;
+            transform.rotation = Quat::from_rotation_y(brain.heading.x.atan2(brain.heading.z));
+
+            continue;
+        }
+
+        match ahead(
+            &store,
+            &origin,
+            feet,
+            brain.heading,
+            body.half_width + LOOKAHEAD,
+        ) {
+            Ahead::Clear => {}
+            Ahead::Ledge => {
+                if body.on_ground {
+                    body.jump = Some(JUMP_VELOCITY);
+                }
+            }
+            Ahead::Wall | Ahead::Water => {
+                brain.turn();
+                continue;
+            }
+        }
+
+        body.wish = brain.heading * speed;
+        transform.rotation = Quat::from_rotation_y(brain.heading.x.atan2(brain.heading.z));
+    }
+}
+
+/// Spawn timing and the mesh shared by every goat.
+pub struct GoatSpawner {
+    timer: Timer,
+    rng: Rng,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for GoatSpawner {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Box::new(
+                GOAT_HALF_WIDTH * 2.,
+                GOAT_HEIGHT,
+                GOAT_HALF_WIDTH * 3.,
+            )));
+
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(Color::rgb(0.9, 0.88, 0.82).into());
+
+        Self {
+            timer: Timer::from_seconds(SPAWN_INTERVAL, true),
+            rng: Rng::new(0x9e37_79b9),
+            mesh,
+            material,
+        }
+    }
+}
+
+/// Top block of a loaded column when it is grass with room above for a goat,
+/// plants standing on it are ignored.
+fn grassy_surface(store: &VoxelStore, column: IVec2) -> Option<IVec3> {
+    let grass = store.registry.id("grass")?;
+
+    for y in (0..CHUNK_HEIGHT).rev() {
+        let position = IVec3::new(column.x, y, column.y);
+        let block = store.block(position)?;
+        let properties = store.registry.get(block);
+
+        if block == BlockId::AIR || !(properties.solid || properties.liquid) {
+            continue;
+        }
+
+        let room = !store.is_solid(position + IVec3::Y) && !store.is_solid(position + IVec3::Y * 2);
+
+        return (block == grass && room).then_some(position);
+    }
+
+    None
+}
+
+/// Spawns goats on grass high enough in the generated terrain to be mountains
+/// around the player, and drops the ones left far behind.
+pub fn spawn_goats(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spawner: ResMut<GoatSpawner>,
+    store: Res<VoxelStore>,
+    origin: Res<WorldOrigin>,
+    players: Query<&PlayerPosition>,
+    goats: Query<(Entity, &Transform), With<Goat>>,
+) {
+    if !spawner.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let player = match players.get_single() {
+        Ok(position) => position.0,
+        Err(_) => return,
+    };
+
+    let center = origin.local(&player);
+    let mut count = 0;
+
+    for (entity, transform) in goats.iter() {
+        if transform.translation.distance(center) > DESPAWN_DISTANCE {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            count += 1;
+        }
+    }
+
+    if count >= MAX_GOATS {
+        return;
+    }
+
+    let column = match player.block_pos() {
+        Some(block) => block.0,
+        None => return,
+    };
+    let mut candidates = Vec::new();
+
+    for _ in 0..SPAWN_ATTEMPTS {
+        let angle = spawner.rng.unit() * TAU;
+        let distance =
+            SPAWN_MIN_DISTANCE + spawner.rng.unit() * (SPAWN_MAX_DISTANCE - SPAWN_MIN_DISTANCE);
+
+        let offset = (Vec2::new(angle.cos(), angle.sin()) * distance).as_ivec2();
+
+        if let Some(ground) = grassy_surface(&store, IVec2::new(column.x, column.z) + offset) {
+            if Biome::from_height(ground.y) == Biome::Mountains {
+                candidates.push(ground);
+            }
+        }
+    }
+
+    for ground in candidates.iter() {
+        if count >= MAX_GOATS {
+            break;
+        }
+
+        let feet = origin.to_local(*ground + IVec3::Y).as_vec3() + Vec3::new(0.5, 0., 0.5);
+        let seed = spawner.rng.next();
+
+        commands
+            .spawn_bundle(SpatialBundle {
+                transform: Transform::from_translation(feet),
+                ..default()
+            })
+            .insert_bundle(goat_bundle(seed))
+            .insert(WorldAnchored)
+            .with_children(|parent| {
+                parent.spawn_bundle(PbrBundle {
+                    mesh: spawner.mesh.clone(),
+                    material: spawner.material.clone(),
+                    transform: Transform::from_xyz(0., GOAT_HEIGHT / 2., 0.),
+                    ..default()
+                });
+            });
+
+        count += 1;
+    }
+}
+
+pub struct GoatPlugin;
+
+impl Plugin for GoatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GoatSpawner>().add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(spawn_goats)
+                .with_system(goat_think),
+        );
+    }
+}
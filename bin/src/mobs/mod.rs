@@ -0,0 +1,135 @@
+use bevy::{prelude::*, time::FixedTimestep};
+
+use client::chunk::VoxelStore;
+use client::coords::BlockPos;
+use client::origin::WorldOrigin;
+use client::physics::{sweep_step, Aabb};
+
+use crate::world::state::while_playing;
+
+pub mod goat;
+pub mod pathfinding;
+
+const PHYSICS_STEP: f32 = 1. / 60.;
+const GRAVITY: f32 = 32.;
+const TERMINAL_VELOCITY: f32 = 60.;
+
+/// Xorshift generator, mobs only need cheap and repeatable randomness.
+#[derive(Clone, Copy, Debug)]
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        // zero is the one state xorshift never leaves
+        Self(seed.max(1))
+    }
+
+    pub fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn unit(&mut self) -> f32 {
+        (self.next() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+/// Body of a mob, its transform sits at the feet. Mobs have no step height,
+/// ledges are jumped.
+#[derive(Component)]
+pub struct MobBody {
+    pub half_width: f32,
+    pub height: f32,
+    pub velocity: Vec3,
+    pub on_ground: bool,
+    /// Horizontal velocity the mob walks at.
+    pub wish: Vec3,
+    /// Vertical velocity given on the next step spent on the ground.
+    pub jump: Option<f32>,
+}
+
+impl MobBody {
+    pub fn new(half_width: f32, height: f32) -> Self {
+        Self {
+            half_width,
+            height,
+            velocity: Vec3::ZERO,
+            on_ground: false,
+            wish: Vec3::ZERO,
+            jump: None,
+        }
+    }
+
+    pub fn aabb(&self, feet: Vec3) -> Aabb {
+        Aabb::from_feet(feet, self.half_width, self.height)
+    }
+}
+
+/// Moves the mobs, the ones falling out of the bottom of the world are
+/// despawned. Above the top they fall back like anywhere else.
+pub fn mob_physics(
+    mut commands: Commands,
+    store: Res<VoxelStore>,
+    origin: Res<WorldOrigin>,
+    mut mobs: Query<(Entity, &mut Transform, &mut MobBody)>,
+) {
+    for (entity, mut transform, mut body) in mobs.iter_mut() {
+        let feet = origin.to_world(transform.translation.floor().as_ivec3());
+
+        // mobs in chunks not loaded yet wait where they are
+        if !store.is_loaded(BlockPos(feet).chunk().0) {
+            continue;
+        }
+
+        if feet.y < 0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        body.velocity.x = body.wish.x;
+        body.velocity.z = body.wish.z;
+
+        if body.on_ground {
+            if let Some(jump) = body.jump.take() {
+                body.velocity.y = jump;
+            }
+        }
+
+        body.velocity.y = (body.velocity.y - GRAVITY * PHYSICS_STEP).max(-TERMINAL_VELOCITY);
+
+        let (offset, hit) = sweep_step(
+            &body.aabb(transform.translation),
+            body.velocity * PHYSICS_STEP,
+            0.,
+            body.on_ground,
+            |position| store.is_solid(origin.to_world(position)),
+        );
+
+        transform.translation += offset;
+
+        body.on_ground = hit.y && body.velocity.y < 0.;
+
+        if hit.y {
+            body.velocity.y = 0.;
+        }
+    }
+}
+
+pub struct MobsPlugin;
+
+impl Plugin for MobsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(goat::GoatPlugin)
+            .add_plugin(pathfinding::PathfindingPlugin)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(
+                        FixedTimestep::step(PHYSICS_STEP as f64).chain(while_playing),
+                    )
+                    .with_system(mob_physics),
+            );
+    }
+}
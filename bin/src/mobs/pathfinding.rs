@@ -0,0 +1,96 @@
+use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
+
+use client::chunk::VoxelStore;
+use client::path::{find_path, Agent, PathGrid, MAX_VISITED};
+
+use std::sync::{Arc, Mutex};
+
+/// Blocks copied around the start and the goal of a path, the search never
+/// leaves that box.
+const SEARCH_MARGIN: i32 = 8;
+
+/// Farthest a goal can be from the start along each axis. The box copied on
+/// the frame grows with it, and a search giving up after `MAX_VISITED` cells
+/// seldom gets farther anyway.
+const MAX_SPAN: i32 = 48;
+
+/// Asks for a path for an entity, in world block coordinates. The answer
+/// comes back as a [`PathResponse`] a few frames later.
+#[derive(Clone, Copy, Debug)]
+pub struct PathRequest {
+    pub entity: Entity,
+    /// Cell the feet are in, above the block stood on.
+    pub start: IVec3,
+    pub goal: IVec3,
+    pub agent: Agent,
+}
+
+#[derive(Clone, Debug)]
+pub struct PathResponse {
+    pub entity: Entity,
+    /// Cells from the start to the goal, `None` when out of reach.
+    pub path: Option<Vec<IVec3>>,
+}
+
+/// Paths found on the async compute pool, waiting to be sent.
+#[derive(Default)]
+pub struct PathFinder {
+    found: Arc<Mutex<Vec<PathResponse>>>,
+}
+
+/// Searches each requested path on a copy of the blocks around it, away from
+/// the frame. Goals too far to copy are answered with no path.
+pub fn request_paths(
+    store: Res<VoxelStore>,
+    finder: Res<PathFinder>,
+    mut requests: EventReader<PathRequest>,
+) {
+    let pool = AsyncComputeTaskPool::get();
+
+    for request in requests.iter().copied() {
+        if (request.goal - request.start).abs().max_element() > MAX_SPAN {
+            finder.found.lock().unwrap().push(PathResponse {
+                entity: request.entity,
+                path: None,
+            });
+            continue;
+        }
+
+        let grid = PathGrid::around(&*store, request.start, request.goal, SEARCH_MARGIN);
+        let found = finder.found.clone();
+
+        pool.spawn(async move {
+            let path = find_path(
+                &grid,
+                &request.agent,
+                request.start,
+                request.goal,
+                MAX_VISITED,
+            );
+
+            found.lock().unwrap().push(PathResponse {
+                entity: request.entity,
+                path,
+            });
+        })
+        .detach();
+    }
+}
+
+pub fn send_paths(finder: Res<PathFinder>, mut responses: EventWriter<PathResponse>) {
+    for response in finder.found.lock().unwrap().drain(..) {
+        responses.send(response);
+    }
+}
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PathRequest>()
+            .add_event::<PathResponse>()
+            .init_resource::<PathFinder>()
+            .add_system(request_paths)
+            .add_system(send_paths);
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use client::chunk::VoxelStore;
+use client::definition::{BlockDefinition, ModelDefinition};
+
+use serde::de::DeserializeOwned;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::voxel::terrain::{Chunk, NeedsMesh};
+
+const BLOCKS_DIR: &str = "assets/blocks";
+const MODELS_DIR: &str = "assets/models";
+
+/// Seconds between two checks of the asset folders.
+const RELOAD_INTERVAL: f32 = 1.;
+
+/// Path of a file under `assets`, found like Bevy's asset server does: from
+/// the crate manifest when run through cargo, else from the executable. The
+/// folders above are tried too, the assets are shared by the workspace.
+pub fn asset_path(path: &str) -> PathBuf {
+    let base = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| Some(env::current_exe().ok()?.parent()?.to_path_buf()))
+        .unwrap_or_default();
+
+    base.ancestors()
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.exists())
+        .unwrap_or_else(|| base.join(path))
+}
+
+/// Reads every `.ron` and `.json` file of an asset folder, keyed by file name.
+pub fn read_assets<T: DeserializeOwned>(dir: &str) -> HashMap<String, T> {
+    let mut assets = HashMap::new();
+
+    let entries = match fs::read_dir(asset_path(dir)) {
+        Ok(entries) => entries,
+        Err(_) => return assets,
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => continue,
+        };
+
+        let parsed = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ron") => ron::from_str(&text).map_err(|error| error.to_string()),
+            Some("json") => serde_json::from_str(&text).map_err(|error| error.to_string()),
+            _ => continue,
+        };
+
+        match parsed {
+            Ok(asset) => {
+                assets.insert(name, asset);
+            }
+            Err(error) => warn!("invalid {}: {}", path.display(), error),
+        }
+    }
+
+    assets
+}
+
+/// Files of asset folders and their latest modification time, it changes
+/// when a file is edited, added or deleted.
+#[derive(PartialEq, Debug, Default)]
+pub struct AssetsSnapshot {
+    files: BTreeSet<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl AssetsSnapshot {
+    pub fn take(dirs: &[PathBuf]) -> Self {
+        let mut snapshot = Self::default();
+
+        for entry in dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+        {
+            let modified = entry.metadata().and_then(|metadata| metadata.modified());
+
+            snapshot.modified = snapshot.modified.max(modified.ok());
+            snapshot.files.insert(entry.path());
+        }
+
+        snapshot
+    }
+}
+
+fn block_dirs() -> [PathBuf; 2] {
+    [asset_path(BLOCKS_DIR), asset_path(MODELS_DIR)]
+}
+
+/// Registers the block assets over the built-in blocks. Blocks keep their id
+/// when redefined, so the chunks already generated stay valid.
+///
+/// New blocks are registered in name order: their ids end up in saved
+/// inventories, and must not depend on the order files are listed in.
+/// Deleting a file does not unregister its block: ids are never reused, so it
+/// keeps its last definition until the game restarts.
+pub fn load_blocks(store: &mut VoxelStore) {
+    let models: HashMap<String, ModelDefinition> = read_assets(MODELS_DIR);
+    let blocks: BTreeMap<String, BlockDefinition> = read_assets(BLOCKS_DIR).into_iter().collect();
+
+    for (name, definition) in blocks.iter() {
+        match definition.block(name, &models) {
+            Ok(block) => {
+                store.registry.register(block);
+            }
+            Err(error) => warn!("block {}: {}", name, error),
+        }
+    }
+}
+
+pub struct BlockAssets {
+    timer: Timer,
+    snapshot: AssetsSnapshot,
+}
+
+impl Default for BlockAssets {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(RELOAD_INTERVAL, true),
+            snapshot: AssetsSnapshot::take(&block_dirs()),
+        }
+    }
+}
+
+pub fn setup_blocks(mut store: ResMut<VoxelStore>) {
+    load_blocks(&mut store);
+}
+
+/// Reloads the block assets when a file changes, is added or deleted, and
+/// remeshes every chunk.
+pub fn reload_blocks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut assets: ResMut<BlockAssets>,
+    mut store: ResMut<VoxelStore>,
+    chunks: Query<Entity, With<Chunk>>,
+) {
+    if !assets.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let snapshot = AssetsSnapshot::take(&block_dirs());
+
+    if snapshot == assets.snapshot {
+        return;
+    }
+
+    assets.snapshot = snapshot;
+    load_blocks(&mut store);
+
+    for entity in chunks.iter() {
+        commands.entity(entity).insert(NeedsMesh);
+    }
+}
+
+pub struct BlockAssetsPlugin;
+
+impl Plugin for BlockAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockAssets>()
+            .add_startup_system(setup_blocks)
+            .add_system(reload_blocks);
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::{
+    prelude::*,
+    render::camera::{CameraProjection, Projection},
+    render::view::VisibilitySystems,
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+};
+
+use client::origin::WorldOrigin;
+use client::visibility::{section_aabb, visible_sections, Frustum, SectionVisibility};
+
+use super::terrain::Chunk;
+use crate::controls::camera::PlayerCamera;
+use crate::world::settings::Settings;
+
+/// Hides the chunks outside the view frustum or behind solid ground, walking
+/// the section visibility graphs from the camera outward.
+pub fn cull_chunks(
+    origin: Res<WorldOrigin>,
+    settings: Res<Settings>,
+    cameras: Query<(&GlobalTransform, &Projection), With<PlayerCamera>>,
+    mut chunks: Query<(&Chunk, &mut Visibility)>,
+) {
+    let (transform, projection) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let view = transform.compute_matrix().inverse();
+    let frustum = Frustum::from_matrix(projection.get_projection_matrix() * view);
+
+    let graphs: HashMap<IVec2, &[SectionVisibility]> = chunks
+        .iter()
+        .map(|(chunk, _)| (chunk.position, chunk.sections.as_slice()))
+        .collect();
+
+    // unmeshed chunks are walked through as if they were open
+    let graph = |section: IVec3| {
+        graphs.get(&section.xz()).map(|sections| {
+            sections
+                .get(section.y as usize)
+                .copied()
+                .unwrap_or_else(SectionVisibility::all)
+        })
+    };
+
+    // sections are in world blocks, the frustum is relative to the origin
+    let in_view = |section: IVec3| {
+        let (min, max) = section_aabb(section);
+        let local = origin.to_local(min.as_ivec3()).as_vec3();
+
+        frustum.intersects_aabb(local, local + max - min)
+    };
+
+    let camera = match origin.position(transform.translation()).block_pos() {
+        Some(camera) => camera,
+        None => return,
+    };
+
+    let visible: HashSet<IVec2> =
+        visible_sections(camera, settings.video.view_distance, graph, in_view)
+            .into_iter()
+            .map(|section| section.xz())
+            .collect();
+
+    for (chunk, mut visibility) in chunks.iter_mut() {
+        let shown = visible.contains(&chunk.position);
+
+        if visibility.is_visible != shown {
+            visibility.is_visible = shown;
+        }
+    }
+}
+
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            cull_chunks
+                .after(TransformSystem::TransformPropagate)
+                .before(VisibilitySystems::CheckVisibility),
+        );
+    }
+}
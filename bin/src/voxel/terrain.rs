@@ -1,71 +1,356 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::{HashMap, HashSet, Instant},
+};
 
-use client::mesh::genchunk;
-use client::orientation::{parse_normal, parse_orientation, Orientation};
+use client::chunk::VoxelStore;
+use client::coords::ChunkPos;
+use client::lod::{genlod, lod_scale};
+use client::mesh::{genblocks, meshchunk, padchunk, ChunkMesh};
+use client::origin::WorldOrigin;
+use client::visibility::{chunk_visibility, SectionVisibility};
+
+use crate::world::origin::{PlayerPosition, WorldAnchored};
+use crate::world::save::ActiveWorld;
+use crate::world::settings::Settings;
+use crate::world::state::in_world;
+
+/// Seed of the worlds saved before each had its own.
+pub const SEED: u32 = 2;
+
+/// Chunks kept around the player in each direction, unless set otherwise.
+pub const VIEW_DISTANCE: i32 = 16;
+
+/// Chunks spawned each frame, nearest first.
+const SPAWN_PER_FRAME: usize = 8;
+
+/// Chunk meshes built each frame, nearest first, the rest wait for the next
+/// frames.
+const MESH_PER_FRAME: usize = 8;
 
 #[derive(Component)]
 pub struct Chunk {
-    #[allow(dead_code)]
-    position: Vec2,
+    pub position: IVec2,
+    /// Voxel downsampling of the mesh, 1 for full resolution.
+    pub lod: i32,
+    /// Face connectivity of each section, empty until the chunk is meshed.
+    pub sections: Vec<SectionVisibility>,
 }
 
+/// Chunks whose mesh must be rebuilt from the store.
+#[derive(Component)]
+pub struct NeedsMesh;
+
 #[derive(Component)]
 pub struct TerrainGen;
 
-fn squaregen(
-    size: Vec2,
-    minimum: Vec3,
-    orientation: &Orientation,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    material: Handle<StandardMaterial>,
-) -> PbrBundle {
-    let (position, rotation) = parse_orientation(minimum, size, orientation);
-
-    PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Quad { size, ..default() })),
-        material,
-        transform: Transform {
-            translation: position,
-            rotation,
+/// Entity of every spawned chunk.
+#[derive(Default)]
+pub struct ChunkEntities(pub HashMap<IVec2, Entity>);
+
+impl ChunkEntities {
+    /// Flags the chunks around a changed block, light can spread into the
+    /// neighbouring chunks.
+    pub fn remesh_around(&self, commands: &mut Commands, coord: IVec2) {
+        for x in -1..=1 {
+            for z in -1..=1 {
+                if let Some(entity) = self.0.get(&(coord + IVec2::new(x, z))) {
+                    commands.entity(*entity).insert(NeedsMesh);
+                }
+            }
+        }
+    }
+}
+
+/// Moving average of a duration, in milliseconds.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Timing {
+    pub average: Option<f32>,
+}
+
+impl Timing {
+    /// Weight of the latest sample, older ones fade out.
+    const SMOOTHING: f32 = 0.1;
+
+    pub fn record(&mut self, start: Instant) {
+        let sample = start.elapsed().as_secs_f32() * 1000.;
+
+        self.average = Some(match self.average {
+            Some(average) => average + (sample - average) * Self::SMOOTHING,
+            None => sample,
+        });
+    }
+}
+
+/// Time spent generating the blocks of a chunk and building its mesh.
+#[derive(Default)]
+pub struct ChunkTimings {
+    pub generation: Timing,
+    pub meshing: Timing,
+}
+
+pub struct ChunkMaterial(pub Handle<StandardMaterial>);
+
+impl FromWorld for ChunkMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+
+        Self(materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            perceptual_roughness: 1.,
             ..default()
-        },
-        ..default()
+        }))
     }
 }
 
+fn buildmesh(data: ChunkMesh) -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, data.positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, data.normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, data.uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, data.colors);
+    mesh.set_indices(Some(Indices::U32(data.indices)));
+
+    mesh
+}
+
+/// Spawns a chunk entity, its blocks are generated when it is first meshed at
+/// full resolution.
 pub fn spawnchunk(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    coord: Vec2,
+    material: &ChunkMaterial,
+    chunks: &mut ChunkEntities,
+    origin: &WorldOrigin,
+    position: IVec2,
+    lod: i32,
 ) {
-    let buffer = genchunk(coord.x as i32, coord.y as i32, 2);
+    // mesh vertices are in padded voxel space, shifted by one block
+    let corner = origin.to_local(ChunkPos(position).origin().0) - IVec3::new(1, 0, 1);
 
-    commands
+    let entity = commands
         .spawn_bundle(PbrBundle {
-            transform: Transform::from_xyz(coord.x * 16., 0., coord.y * 16.),
+            material: material.0.clone(),
+            transform: Transform::from_translation(corner.as_vec3()),
             ..default()
         })
-        .with_children(|parent| {
-            for (index, quadbuffer) in buffer.groups.iter().enumerate() {
-                let orientation = parse_normal(index, None);
-
-                for quad in quadbuffer.iter() {
-                    parent.spawn_bundle(squaregen(
-                        Vec2::new(1., 1.),
-                        Vec3::new(
-                            quad.minimum[0] as f32,
-                            quad.minimum[1] as f32,
-                            quad.minimum[2] as f32,
-                        ),
-                        &orientation,
-                        meshes,
-                        materials.add(Color::GREEN.into()),
-                    ));
+        .insert(Chunk {
+            position,
+            lod,
+            sections: Vec::new(),
+        })
+        .insert(NeedsMesh)
+        .insert(WorldAnchored)
+        .id();
+
+    chunks.0.insert(position, entity);
+}
+
+/// Full resolution chunks need the blocks of their neighbours too, to build
+/// the faces and light along their borders. Returns the chunks generated.
+fn loadblocks(
+    store: &mut VoxelStore,
+    timings: &mut ChunkTimings,
+    seed: u32,
+    coord: IVec2,
+) -> Vec<IVec2> {
+    let mut generated = Vec::new();
+
+    for x in -1..=1 {
+        for z in -1..=1 {
+            let position = coord + IVec2::new(x, z);
+
+            if store.is_loaded(position) {
+                continue;
+            }
+
+            let start = Instant::now();
+            let blocks = genblocks(position.x, position.y, seed, &store.registry);
+            store.insert(position, blocks);
+            timings.generation.record(start);
+
+            generated.push(position);
+        }
+    }
+
+    generated
+}
+
+/// Builds the meshes of the chunks flagged with `NeedsMesh`, a few each frame.
+pub fn mesh_chunks(
+    mut commands: Commands,
+    mut store: ResMut<VoxelStore>,
+    chunks: Res<ChunkEntities>,
+    mut timings: ResMut<ChunkTimings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    world: Option<Res<ActiveWorld>>,
+    mut query: Query<(Entity, &mut Chunk, Option<&NeedsMesh>)>,
+) {
+    let seed = match world {
+        Some(world) => world.seed,
+        None => return,
+    };
+
+    // the finest levels of detail are the nearest chunks
+    let mut pending: Vec<(Entity, i32)> = query
+        .iter()
+        .filter(|(_, _, needs)| needs.is_some())
+        .map(|(entity, chunk, _)| (entity, chunk.lod))
+        .collect();
+
+    pending.sort_by_key(|(_, lod)| *lod);
+
+    let mut flagged = HashSet::new();
+
+    for (entity, lod) in pending.into_iter().take(MESH_PER_FRAME) {
+        let position = match query.get(entity) {
+            Ok((_, chunk, _)) => chunk.position,
+            Err(_) => continue,
+        };
+
+        let mesh = if lod == 1 {
+            let generated = loadblocks(&mut store, &mut timings, seed, position);
+
+            // light can spread into the chunks around, the ones already
+            // meshed at full resolution are rebuilt
+            for neighbour in generated.iter().flat_map(|coord| {
+                (-1..=1).flat_map(move |x| (-1..=1).map(move |z| *coord + IVec2::new(x, z)))
+            }) {
+                let other = match chunks.0.get(&neighbour) {
+                    Some(other) if *other != entity => *other,
+                    _ => continue,
+                };
+
+                if let Ok((_, chunk, None)) = query.get(other) {
+                    if !chunk.sections.is_empty() && flagged.insert(other) {
+                        commands.entity(other).insert(NeedsMesh);
+                    }
                 }
             }
-        })
-        .insert(Chunk { position: coord });
+
+            let (_, mut chunk, _) = query.get_mut(entity).unwrap();
+            let start = Instant::now();
+
+            if let Some(data) = store.chunk(position) {
+                chunk.sections = chunk_visibility(data, &store.registry);
+            }
+
+            let voxels = padchunk(&store, position);
+            let mesh = meshchunk(&voxels, &store.registry);
+            timings.meshing.record(start);
+
+            mesh
+        } else {
+            // coarse meshes are drawn from the height function only
+            if let Ok((_, mut chunk, _)) = query.get_mut(entity) {
+                chunk.sections.clear();
+            }
+
+            genlod(position.x, position.y, seed, lod, &store.registry)
+        };
+
+        commands
+            .entity(entity)
+            .insert(meshes.add(buildmesh(mesh)))
+            .remove::<NeedsMesh>();
+    }
+}
+
+/// Keeps the chunks around the player spawned once a world is open, switching
+/// their level of detail as the player moves between rings. Runs before the
+/// update stage so no system flags a chunk despawned in the same frame.
+pub fn stream_chunks(
+    mut commands: Commands,
+    material: Res<ChunkMaterial>,
+    mut chunks: ResMut<ChunkEntities>,
+    origin: Res<WorldOrigin>,
+    settings: Res<Settings>,
+    players: Query<&PlayerPosition>,
+    mut query: Query<&mut Chunk>,
+) {
+    let center = match players
+        .get_single()
+        .ok()
+        .and_then(|position| position.0.block_pos())
+    {
+        Some(block) => block.chunk(),
+        None => return,
+    };
+
+    let distance = |position: IVec2| center.distance(ChunkPos(position));
+    let view_distance = settings.video.view_distance;
+
+    let mut unloaded = Vec::new();
+
+    for (position, entity) in chunks.0.iter() {
+        if distance(*position) > view_distance {
+            commands.entity(*entity).despawn_recursive();
+            unloaded.push(*position);
+            continue;
+        }
+
+        if let Ok(mut chunk) = query.get_mut(*entity) {
+            let lod = lod_scale(distance(*position));
+
+            if chunk.lod != lod {
+                chunk.lod = lod;
+                commands.entity(*entity).insert(NeedsMesh);
+            }
+        }
+    }
+
+    for position in unloaded {
+        chunks.0.remove(&position);
+    }
+
+    let mut missing: Vec<IVec2> = center
+        .around(view_distance)
+        .map(IVec2::from)
+        .filter(|position| !chunks.0.contains_key(position))
+        .collect();
+
+    missing.sort_by_key(|position| distance(*position));
+
+    for position in missing.into_iter().take(SPAWN_PER_FRAME) {
+        spawnchunk(
+            &mut commands,
+            &material,
+            &mut chunks,
+            &origin,
+            position,
+            lod_scale(distance(position)),
+        );
+    }
+}
+
+/// Unloads the blocks of chunks past the view distance, chunks the player
+/// edited stay loaded so the edits are not lost. One more ring is kept, full
+/// resolution chunks need the blocks of their neighbours.
+pub fn evict_chunks(
+    mut store: ResMut<VoxelStore>,
+    settings: Res<Settings>,
+    players: Query<&PlayerPosition>,
+) {
+    let center = match players
+        .get_single()
+        .ok()
+        .and_then(|position| position.0.block_pos())
+    {
+        Some(block) => block.chunk(),
+        None => return,
+    };
+
+    let keep = settings.video.view_distance + 1;
+    let distant: Vec<IVec2> = store
+        .coords()
+        .filter(|coord| center.distance(ChunkPos(*coord)) > keep)
+        .filter(|coord| !store.is_edited(*coord))
+        .collect();
+
+    for coord in distant {
+        store.remove(coord);
+    }
 }
 
 pub fn generation(
@@ -73,44 +358,31 @@ pub fn generation(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    commands.spawn_bundle(PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Cube { size: 1. })),
-        material: materials.add(Color::YELLOW.into()),
-        transform: Transform::from_xyz(0.5, 0.5, 0.5),
-        ..default()
-    });
-
-    spawnchunk(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec2::new(0., 0.),
-    );
-
-    spawnchunk(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec2::new(-1., 0.),
-    );
-
-    spawnchunk(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec2::new(-1., -1.),
-    );
-
-    spawnchunk(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        Vec2::new(0., -1.),
-    )
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1. })),
+            material: materials.add(Color::YELLOW.into()),
+            transform: Transform::from_xyz(0.5, 0.5, 0.5),
+            ..default()
+        })
+        .insert(WorldAnchored);
 }
 
 impl Plugin for TerrainGen {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(generation);
+        app.init_resource::<VoxelStore>()
+            .init_resource::<ChunkEntities>()
+            .init_resource::<ChunkTimings>()
+            .init_resource::<ChunkMaterial>()
+            .add_startup_system(generation)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                stream_chunks.with_run_criteria(in_world),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                evict_chunks.with_run_criteria(in_world),
+            )
+            .add_system(mesh_chunks);
     }
 }
@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+use client::chunk::VoxelStore;
+use client::crafting::{RecipeBook, RecipeDefinition};
+
+use std::collections::HashMap;
+
+use crate::voxel::blocks::read_assets;
+
+const RECIPES_DIR: &str = "assets/recipes";
+
+/// Builds the recipe book once the block assets are registered.
+pub fn load_recipes(mut commands: Commands, store: Res<VoxelStore>) {
+    let definitions: HashMap<String, RecipeDefinition> = read_assets(RECIPES_DIR);
+    let mut book = RecipeBook::default();
+
+    for (name, definition) in definitions.iter() {
+        match definition.recipe(&store.registry) {
+            Ok(recipe) => book.add(recipe),
+            Err(error) => warn!("recipe {}: {}", name, error),
+        }
+    }
+
+    commands.insert_resource(book);
+}
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeBook>()
+            .add_startup_system_to_stage(StartupStage::PostStartup, load_recipes);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use client::time::WorldTime;
+
+use crate::world::state::GameState;
+
+const SUN_ILLUMINANCE: f32 = 32000.;
+const MOON_ILLUMINANCE: f32 = 1500.;
+
+const DAY_SKY: Vec3 = Vec3::new(0.5, 0.8, 0.9);
+const NIGHT_SKY: Vec3 = Vec3::new(0.01, 0.01, 0.05);
+const TWILIGHT_SKY: Vec3 = Vec3::new(0.9, 0.45, 0.25);
+
+const DAY_AMBIENT: f32 = 0.3;
+const NIGHT_AMBIENT: f32 = 0.03;
+
+/// How fast `WorldTime` moves forward.
+pub struct DayCycle {
+    /// Real seconds for a full day.
+    pub day_length: f32,
+    pub paused: bool,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self {
+            day_length: 1200.,
+            paused: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Sun;
+
+#[derive(Component)]
+pub struct Moon;
+
+pub fn spawn_sky(mut commands: Commands) {
+    commands
+        .spawn_bundle(DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: SUN_ILLUMINANCE,
+                shadows_enabled: true,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Sun);
+
+    commands
+        .spawn_bundle(DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: MOON_ILLUMINANCE,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Moon);
+}
+
+pub fn advance_time(cycle: Res<DayCycle>, time: Res<Time>, mut world_time: ResMut<WorldTime>) {
+    if !cycle.paused {
+        world_time.advance(time.delta_seconds(), cycle.day_length);
+    }
+}
+
+pub fn update_sky(
+    world_time: Res<WorldTime>,
+    mut clear: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    mut suns: Query<(&mut Transform, &mut DirectionalLight), (With<Sun>, Without<Moon>)>,
+    mut moons: Query<(&mut Transform, &mut DirectionalLight), (With<Moon>, Without<Sun>)>,
+) {
+    let sun = world_time.sun_direction();
+    let daylight = world_time.daylight();
+
+    // lights shine along their forward axis, away from the body
+    for (mut transform, mut light) in suns.iter_mut() {
+        *transform = Transform::default().looking_at(-sun, Vec3::Z);
+        light.illuminance = SUN_ILLUMINANCE * daylight;
+    }
+
+    for (mut transform, mut light) in moons.iter_mut() {
+        *transform = Transform::default().looking_at(sun, Vec3::Z);
+        light.illuminance = MOON_ILLUMINANCE * (1. - daylight);
+    }
+
+    let twilight = (1. - sun.y.abs() / 0.25).max(0.) * 0.6;
+    let sky = NIGHT_SKY
+        .lerp(DAY_SKY, daylight)
+        .lerp(TWILIGHT_SKY, twilight);
+
+    clear.0 = Color::rgb(sky.x, sky.y, sky.z);
+    ambient.brightness = NIGHT_AMBIENT + (DAY_AMBIENT - NIGHT_AMBIENT) * daylight;
+}
+
+pub struct DayCyclePlugin;
+
+impl Plugin for DayCyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DayCycle>()
+            .init_resource::<WorldTime>()
+            .add_startup_system(spawn_sky)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(advance_time))
+            .add_system(update_sky);
+    }
+}
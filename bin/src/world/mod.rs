@@ -0,0 +1,6 @@
+pub mod crafting;
+pub mod daycycle;
+pub mod origin;
+pub mod save;
+pub mod settings;
+pub mod state;
@@ -0,0 +1,66 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use client::origin::{WorldOrigin, WorldPos};
+
+/// Horizontal distance from the origin, in blocks, past which every transform
+/// is moved back around the player.
+const REBASE_DISTANCE: f32 = 512.;
+
+/// Precise position of the player, movement is applied to it. The transform
+/// is only a copy relative to the current `WorldOrigin`.
+#[derive(Component, Default)]
+pub struct PlayerPosition(pub WorldPos);
+
+/// Root entity placed in the world, its transform is shifted along when the
+/// origin moves. UI, lights and the camera are left alone.
+#[derive(Component)]
+pub struct WorldAnchored;
+
+/// Places the transform of every player at its precise position.
+pub fn place_players(
+    origin: Res<WorldOrigin>,
+    mut players: Query<(&mut Transform, &PlayerPosition)>,
+) {
+    for (mut transform, position) in players.iter_mut() {
+        transform.translation = origin.local(&position.0);
+    }
+}
+
+/// Moves the origin under the player once it wanders too far, shifting every
+/// anchored transform so nothing moves in the world.
+pub fn rebase_origin(
+    mut origin: ResMut<WorldOrigin>,
+    players: Query<&PlayerPosition>,
+    mut transforms: Query<&mut Transform, (With<WorldAnchored>, Without<Parent>)>,
+) {
+    let position = match players.get_single() {
+        Ok(position) => position,
+        Err(_) => return,
+    };
+
+    let rebased = match origin.rebased(origin.local(&position.0), REBASE_DISTANCE) {
+        Some(rebased) => rebased,
+        None => return,
+    };
+
+    let shift = origin.shift(&rebased);
+
+    for mut transform in transforms.iter_mut() {
+        transform.translation += shift;
+    }
+
+    *origin = rebased;
+}
+
+pub struct FloatingOriginPlugin;
+
+impl Plugin for FloatingOriginPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldOrigin>()
+            .add_system_to_stage(CoreStage::First, rebase_origin)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                place_players.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
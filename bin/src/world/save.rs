@@ -0,0 +1,233 @@
+use bevy::{app::AppExit, prelude::*};
+
+use client::block::BlockRegistry;
+use client::chunk::VoxelStore;
+use client::inventory::Inventory;
+use client::origin::WorldOrigin;
+use client::time::WorldTime;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::controls::hotbar::starter_inventory;
+use crate::controls::player::{MovementMode, PlayerController};
+use crate::mobs::MobBody;
+use crate::voxel::terrain::{Chunk, ChunkEntities, SEED};
+use crate::world::state::GameState;
+
+const SAVES_DIR: &str = "saves";
+const LEVEL_FILE: &str = "level.ron";
+
+/// Longest name a world can be given.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+const AUTOSAVE_INTERVAL: f32 = 60.;
+
+/// Everything about the world kept between sessions.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelData {
+    /// Terrain seed, worlds saved before they had one used `SEED`.
+    pub seed: u32,
+    pub time: WorldTime,
+    /// `None` until the world is first saved.
+    pub inventory: Option<Inventory>,
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        Self {
+            seed: SEED,
+            time: WorldTime::default(),
+            inventory: None,
+        }
+    }
+}
+
+fn level_path(name: &str) -> PathBuf {
+    PathBuf::from(SAVES_DIR).join(name).join(LEVEL_FILE)
+}
+
+impl LevelData {
+    pub fn load(name: &str) -> Self {
+        let path = level_path(name);
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Self::default(),
+        };
+
+        match ron::from_str(&text) {
+            Ok(level) => level,
+            Err(error) => {
+                warn!("invalid {}: {}", path.display(), error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, name: &str) {
+        let path = level_path(name);
+        let text =
+            ron::ser::to_string_pretty(self, default()).expect("level data is always serializable");
+
+        if let Err(error) = fs::create_dir_all(PathBuf::from(SAVES_DIR).join(name))
+            .and_then(|_| fs::write(&path, text))
+        {
+            warn!("could not write {}: {}", path.display(), error);
+        }
+    }
+}
+
+/// World being played, named after its directory in `saves`.
+pub struct ActiveWorld {
+    pub name: String,
+    pub seed: u32,
+}
+
+/// Names of the saved worlds, sorted.
+pub fn list_worlds() -> Vec<String> {
+    let entries = match fs::read_dir(SAVES_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(LEVEL_FILE).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// Name a new world is saved under, `None` when nothing usable is left of
+/// `text`. Only letters, digits, spaces, `-` and `_` are kept so the name is
+/// a valid directory everywhere.
+pub fn world_name(text: &str) -> Option<String> {
+    let name: String = text
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    let name = name.trim();
+
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Seed typed when creating a world. Numbers are taken as they are, other
+/// text is hashed, and `fallback` is used when nothing was typed.
+pub fn parse_seed(text: &str, fallback: u32) -> u32 {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return fallback;
+    }
+
+    // FNV-1a, the same text must give the same world on every build
+    text.parse().unwrap_or_else(|_| {
+        text.bytes().fold(0x811c9dc5, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        })
+    })
+}
+
+/// Makes `name` the world being played, with its saved time and inventory.
+pub fn open_world(commands: &mut Commands, registry: &BlockRegistry, name: &str, level: LevelData) {
+    let inventory = level
+        .inventory
+        .unwrap_or_else(|| starter_inventory(registry));
+
+    commands.insert_resource(inventory);
+    commands.insert_resource(level.time);
+    commands.insert_resource(ActiveWorld {
+        name: name.to_string(),
+        seed: level.seed,
+    });
+}
+
+/// Writes the world being played to its save.
+fn write_level(world: &ActiveWorld, world_time: &WorldTime, inventory: &Inventory) {
+    LevelData {
+        seed: world.seed,
+        time: *world_time,
+        inventory: Some(inventory.clone()),
+    }
+    .save(&world.name);
+}
+
+/// Despawns the player, the mobs and the chunks of the world being left and
+/// unloads its blocks, so the next world opens from scratch.
+pub fn close_world(
+    mut commands: Commands,
+    mut store: ResMut<VoxelStore>,
+    mut chunks: ResMut<ChunkEntities>,
+    mut origin: ResMut<WorldOrigin>,
+    mut movement: ResMut<MovementMode>,
+    entities: Query<Entity, Or<(With<Chunk>, With<MobBody>, With<PlayerController>)>>,
+) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    store.clear();
+    chunks.0.clear();
+    *origin = WorldOrigin::default();
+    *movement = MovementMode::default();
+
+    commands.remove_resource::<ActiveWorld>();
+}
+
+pub fn save_world(
+    world: Option<Res<ActiveWorld>>,
+    world_time: Res<WorldTime>,
+    inventory: Res<Inventory>,
+) {
+    if let Some(world) = world {
+        write_level(&world, &world_time, &inventory);
+    }
+}
+
+pub struct Autosave(Timer);
+
+pub fn save_level(
+    time: Res<Time>,
+    mut autosave: ResMut<Autosave>,
+    mut exit: EventReader<AppExit>,
+    world: Option<Res<ActiveWorld>>,
+    world_time: Res<WorldTime>,
+    inventory: Res<Inventory>,
+) {
+    let exiting = exit.iter().next().is_some();
+
+    let world = match world {
+        Some(world) => world,
+        None => return,
+    };
+
+    if !autosave.0.tick(time.delta()).just_finished() && !exiting {
+        return;
+    }
+
+    write_level(&world, &world_time, &inventory);
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .init_resource::<WorldTime>()
+            .insert_resource(Autosave(Timer::from_seconds(AUTOSAVE_INTERVAL, true)))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(save_world)
+                    .with_system(close_world.after(save_world)),
+            )
+            .add_system_to_stage(CoreStage::Last, save_level);
+    }
+}
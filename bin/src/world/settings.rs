@@ -0,0 +1,335 @@
+use bevy::{
+    prelude::*,
+    render::camera::Projection,
+    window::{PresentMode, WindowMode},
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+use crate::controls::camera::PlayerCamera;
+use crate::controls::input::{BindingsConfig, InputMap};
+use crate::voxel::terrain::VIEW_DISTANCE;
+
+const SETTINGS_DIR: &str = "config";
+const SETTINGS_PATH: &str = "config/settings.ron";
+
+const MIN_RESOLUTION: Vec2 = Vec2::new(640., 480.);
+const MAX_RESOLUTION: Vec2 = Vec2::new(7680., 4320.);
+const MIN_FOV: f32 = 30.;
+const MAX_FOV: f32 = 110.;
+const MIN_VIEW_DISTANCE: i32 = 2;
+const MAX_VIEW_DISTANCE: i32 = 32;
+const MIN_SENSITIVITY: f32 = 0.01;
+const MAX_SENSITIVITY: f32 = 2.;
+const MAX_DEADZONE: f32 = 0.9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    /// Fullscreen window at the desktop resolution.
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub mode: DisplayMode,
+    /// Window size when windowed, in logical pixels.
+    pub width: f32,
+    pub height: f32,
+    pub vsync: bool,
+    /// Vertical field of view, in degrees.
+    pub fov: f32,
+    /// Chunks kept around the player, in each direction.
+    pub view_distance: i32,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Borderless,
+            width: 1920.,
+            height: 1080.,
+            vsync: true,
+            fov: 45.,
+            view_distance: VIEW_DISTANCE,
+        }
+    }
+}
+
+impl VideoSettings {
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+/// Everything the player can change, kept in `config/settings.ron`. Systems
+/// pick up changes to the resource at runtime, and they are saved.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub video: VideoSettings,
+    pub controls: BindingsConfig,
+}
+
+/// Value brought back within `min..=max`, noting it in `problems` when it was
+/// not, NaN becoming `min`.
+fn clamped(value: f32, min: f32, max: f32, name: &str, problems: &mut Vec<String>) -> f32 {
+    if (min..=max).contains(&value) {
+        return value;
+    }
+
+    problems.push(format!("{} {} out of {}..={}", name, value, min, max));
+
+    if value.is_nan() {
+        min
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+/// What was wrong with the settings file at startup, logged once logging is
+/// set up.
+#[derive(Default)]
+pub struct SettingsProblems(pub Vec<String>);
+
+impl Settings {
+    /// Settings as saved, brought back in range. This runs before logging is
+    /// set up, so what was wrong is returned to be reported later.
+    pub fn load() -> (Self, SettingsProblems) {
+        let mut problems = Vec::new();
+
+        let mut settings = match fs::read_to_string(SETTINGS_PATH) {
+            Ok(text) => match ron::from_str(&text) {
+                Ok(settings) => settings,
+                Err(error) => {
+                    problems.push(format!("invalid {}: {}", SETTINGS_PATH, error));
+                    Self::default()
+                }
+            },
+            // bindings used to be saved on their own
+            Err(_) => Self {
+                controls: InputMap::load().to_config(),
+                ..default()
+            },
+        };
+
+        problems.extend(settings.validate());
+
+        (settings, SettingsProblems(problems))
+    }
+
+    pub fn save(&self) {
+        let text =
+            ron::ser::to_string_pretty(self, default()).expect("settings are always serializable");
+
+        if let Err(error) =
+            fs::create_dir_all(SETTINGS_DIR).and_then(|_| fs::write(SETTINGS_PATH, text))
+        {
+            warn!("could not write {}: {}", SETTINGS_PATH, error);
+        }
+    }
+
+    /// Brings every value back in range, returns what was out of it.
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let video = &mut self.video;
+
+        video.width = clamped(
+            video.width,
+            MIN_RESOLUTION.x,
+            MAX_RESOLUTION.x,
+            "width",
+            &mut problems,
+        );
+        video.height = clamped(
+            video.height,
+            MIN_RESOLUTION.y,
+            MAX_RESOLUTION.y,
+            "height",
+            &mut problems,
+        );
+        video.fov = clamped(video.fov, MIN_FOV, MAX_FOV, "fov", &mut problems);
+
+        if !(MIN_VIEW_DISTANCE..=MAX_VIEW_DISTANCE).contains(&video.view_distance) {
+            problems.push(format!(
+                "view distance {} out of {}..={}",
+                video.view_distance, MIN_VIEW_DISTANCE, MAX_VIEW_DISTANCE
+            ));
+            video.view_distance = video
+                .view_distance
+                .clamp(MIN_VIEW_DISTANCE, MAX_VIEW_DISTANCE);
+        }
+
+        let mouse = &mut self.controls.mouse;
+        mouse.sensitivity = clamped(
+            mouse.sensitivity,
+            MIN_SENSITIVITY,
+            MAX_SENSITIVITY,
+            "sensitivity",
+            &mut problems,
+        );
+
+        let sticks = &mut self.controls.sticks;
+        sticks.deadzone = clamped(sticks.deadzone, 0., MAX_DEADZONE, "deadzone", &mut problems);
+
+        problems
+    }
+
+    /// Changes a setting from the console, named as in the settings file
+    /// without its section. Values out of range are refused.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value {} for {}", value, name);
+        let number = || value.parse::<f32>().map_err(|_| invalid());
+        let flag = || match value {
+            "on" | "true" => Ok(true),
+            "off" | "false" => Ok(false),
+            _ => Err(invalid()),
+        };
+
+        let mut changed = self.clone();
+        let video = &mut changed.video;
+        let controls = &mut changed.controls;
+
+        match name {
+            "mode" => {
+                video.mode = match value {
+                    "windowed" => DisplayMode::Windowed,
+                    "borderless" => DisplayMode::Borderless,
+                    "fullscreen" => DisplayMode::Fullscreen,
+                    _ => return Err(invalid()),
+                }
+            }
+            "width" => video.width = number()?,
+            "height" => video.height = number()?,
+            "vsync" => video.vsync = flag()?,
+            "fov" => video.fov = number()?,
+            "view_distance" => video.view_distance = value.parse().map_err(|_| invalid())?,
+            "sensitivity" => controls.mouse.sensitivity = number()?,
+            "invert_y" => controls.mouse.invert_y = flag()?,
+            "deadzone" => controls.sticks.deadzone = number()?,
+            _ => return Err(format!("unknown setting {}", name)),
+        }
+
+        let problems = changed.validate();
+
+        if !problems.is_empty() {
+            return Err(problems.join(", "));
+        }
+
+        *self = changed;
+        Ok(())
+    }
+
+    /// Window to open with these settings, validated first.
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        let video = &self.video;
+        let windowed = video.mode == DisplayMode::Windowed;
+
+        WindowDescriptor {
+            title: "GoatLand".to_string(),
+            width: video.width,
+            height: video.height,
+            present_mode: video.present_mode(),
+            resizable: windowed,
+            decorations: windowed,
+            canvas: Some("#canvas".to_string()),
+            fit_canvas_to_parent: true,
+            mode: video.mode.window_mode(),
+            ..default()
+        }
+    }
+}
+
+/// Applies the settings to the window, the camera and the bindings when they
+/// change, saving them past the first frame. Values out of range are clamped
+/// in the resource first, and applied on the next frame.
+pub fn apply_settings(
+    mut settings: ResMut<Settings>,
+    mut windows: ResMut<Windows>,
+    mut map: ResMut<InputMap>,
+    mut cameras: Query<&mut Projection, With<PlayerCamera>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let mut validated = settings.clone();
+    let problems = validated.validate();
+
+    if !problems.is_empty() {
+        for problem in problems {
+            warn!("settings: {}", problem);
+        }
+
+        *settings = validated;
+        return;
+    }
+
+    if !settings.is_added() {
+        settings.save();
+    }
+
+    let video = &settings.video;
+
+    if let Some(window) = windows.get_primary_mut() {
+        let mode = video.mode.window_mode();
+
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+
+        if video.mode == DisplayMode::Windowed
+            && (window.width() != video.width || window.height() != video.height)
+        {
+            window.set_resolution(video.width, video.height);
+        }
+
+        if window.present_mode() != video.present_mode() {
+            window.set_present_mode(video.present_mode());
+        }
+    }
+
+    for mut projection in cameras.iter_mut() {
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov = video.fov.to_radians();
+        }
+    }
+
+    *map = InputMap::from_config(settings.controls.clone());
+}
+
+/// Logs what was wrong with the settings file, once logging is set up.
+pub fn report_settings(problems: Res<SettingsProblems>) {
+    for problem in &problems.0 {
+        warn!("settings: {}", problem);
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<SettingsProblems>()
+            .add_startup_system(report_settings)
+            .add_system(apply_settings);
+    }
+}
@@ -0,0 +1,179 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+
+use client::chunk::VoxelStore;
+use client::origin::WorldPos;
+
+use crate::controls::console::UiFont;
+use crate::controls::player::PlayerController;
+use crate::voxel::terrain::{ChunkEntities, NeedsMesh};
+use crate::world::origin::PlayerPosition;
+
+/// Chunks around the spawn meshed before the game starts, in each direction.
+const LOADING_RADIUS: i32 = 2;
+
+const FONT_SIZE: f32 = 24.;
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+
+/// Screen the game is on. `Paused` is pushed over `Playing`, the world keeps
+/// its state underneath.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameState {
+    MainMenu,
+    /// Lists the saved worlds and creates new ones.
+    WorldSelect,
+    /// Waits for the chunks around the spawn.
+    Loading,
+    Playing,
+    Paused,
+}
+
+/// Runs while a world is open, paused or not.
+pub fn in_world(state: Res<State<GameState>>) -> ShouldRun {
+    match state.current() {
+        GameState::Loading | GameState::Playing | GameState::Paused => ShouldRun::Yes,
+        GameState::MainMenu | GameState::WorldSelect => ShouldRun::No,
+    }
+}
+
+/// Fixed timestep criteria chained with this one only step while playing.
+/// Steps are still consumed otherwise, so none pile up for when the game
+/// resumes.
+pub fn while_playing(In(input): In<ShouldRun>, state: Res<State<GameState>>) -> ShouldRun {
+    if *state.current() == GameState::Playing {
+        return input;
+    }
+
+    match input {
+        ShouldRun::YesAndCheckAgain | ShouldRun::NoAndCheckAgain => ShouldRun::NoAndCheckAgain,
+        ShouldRun::Yes | ShouldRun::No => ShouldRun::No,
+    }
+}
+
+fn set_cursor_grab(windows: &mut Windows, grab: bool) {
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_lock_mode(grab);
+        window.set_cursor_visibility(!grab);
+    }
+}
+
+pub fn grab_cursor(mut windows: ResMut<Windows>) {
+    set_cursor_grab(&mut windows, true);
+}
+
+pub fn release_cursor(mut windows: ResMut<Windows>) {
+    set_cursor_grab(&mut windows, false);
+}
+
+/// Despawns the screen marked with `T`, when leaving its state.
+pub fn despawn_screen<T: Component>(mut commands: Commands, screens: Query<Entity, With<T>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Component)]
+pub struct LoadingScreen;
+
+#[derive(Component)]
+pub struct LoadingText;
+
+pub fn spawn_loading_screen(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "Loading world",
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(LoadingText);
+        });
+}
+
+/// Starts the game once the chunks around the player are meshed, setting the
+/// player down on the ground.
+pub fn wait_for_chunks(
+    mut state: ResMut<State<GameState>>,
+    store: Res<VoxelStore>,
+    chunks: Res<ChunkEntities>,
+    meshing: Query<(), With<NeedsMesh>>,
+    mut players: Query<&mut PlayerPosition, With<PlayerController>>,
+    mut texts: Query<&mut Text, With<LoadingText>>,
+) {
+    let mut position = match players.get_single_mut() {
+        Ok(position) => position,
+        Err(_) => return,
+    };
+
+    let block = match position.0.block_pos() {
+        Some(block) => block,
+        None => return,
+    };
+    let around: Vec<IVec2> = block
+        .chunk()
+        .around(LOADING_RADIUS)
+        .map(IVec2::from)
+        .collect();
+
+    let ready = around
+        .iter()
+        .filter(|chunk| {
+            chunks
+                .0
+                .get(chunk)
+                .map_or(false, |entity| meshing.get(*entity).is_err())
+        })
+        .count();
+
+    if ready < around.len() {
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = format!("Loading world {}/{}", ready, around.len());
+        }
+
+        return;
+    }
+
+    if let Some(ground) = store.surface(IVec2::new(block.0.x, block.0.z)) {
+        let [x, _, z] = position.0.block;
+        let offset = position.0.offset * Vec3::new(1., 0., 1.);
+
+        position.0 = WorldPos::new([x, ground as i64 + 1, z], offset);
+    }
+
+    // the game may already be leaving the loading screen
+    let _ = state.set(GameState::Playing);
+}
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(GameState::MainMenu)
+            .init_resource::<UiFont>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Loading).with_system(spawn_loading_screen),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Loading).with_system(wait_for_chunks))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Loading).with_system(despawn_screen::<LoadingScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(grab_cursor))
+            .add_system_set(SystemSet::on_resume(GameState::Playing).with_system(grab_cursor))
+            .add_system_set(SystemSet::on_pause(GameState::Playing).with_system(release_cursor));
+    }
+}
@@ -0,0 +1,24 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use client::mesh::{genchunk, genvoxels, genvoxels_per_voxel};
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("genvoxels");
+
+    group.bench_function("per voxel", |b| {
+        b.iter(|| genvoxels_per_voxel(black_box(3), black_box(-2), 0))
+    });
+
+    group.bench_function("heightmap", |b| {
+        b.iter(|| genvoxels(black_box(3), black_box(-2), 0))
+    });
+
+    group.finish();
+
+    c.bench_function("genchunk", |b| {
+        b.iter(|| genchunk(black_box(3), black_box(-2), 0))
+    });
+}
+
+criterion_group!(benches, generation);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use client::noisemap::{genmap, sample_region, sample_region_serial, Region};
+
+fn sampling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("genmap");
+    group.sample_size(10);
+
+    let region = Region::default();
+
+    group.bench_function("plane map builder", |b| b.iter(genmap));
+
+    group.bench_function("region serial", |b| {
+        b.iter(|| sample_region_serial(&region))
+    });
+
+    group.bench_function("region parallel", |b| b.iter(|| sample_region(&region)));
+
+    group.finish();
+}
+
+criterion_group!(benches, sampling);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use bevy_math::IVec2;
+
+use crate::mesh::{TERRAIN_AMPLITUDE, TERRAIN_BASE};
+use crate::noisemap::sample_points;
+
+/// World blocks spanned by one unit of planet coordinates, `genmap` covering
+/// two units on each axis.
+pub const BLOCKS_PER_UNIT: f64 = 4096.;
+
+/// Planet elevation above which the land turns to hills, then mountains.
+const HILLS_LEVEL: f64 = 0.25;
+const MOUNTAINS_LEVEL: f64 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Biome {
+    Ocean,
+    Plains,
+    Hills,
+    Mountains,
+}
+
+impl Biome {
+    /// Biome of a planet elevation, the sea level being 0.
+    pub fn from_elevation(elevation: f64) -> Self {
+        if elevation < 0. {
+            Biome::Ocean
+        } else if elevation < HILLS_LEVEL {
+            Biome::Plains
+        } else if elevation < MOUNTAINS_LEVEL {
+            Biome::Hills
+        } else {
+            Biome::Mountains
+        }
+    }
+
+    /// Biome of a generated terrain column from the height of its surface,
+    /// scaled like a planet elevation. The terrain has no sea, its lowest
+    /// ground is plains.
+    pub fn from_height(height: i32) -> Self {
+        let elevation = (height - TERRAIN_BASE as i32) as f64 / TERRAIN_AMPLITUDE as f64;
+
+        Self::from_elevation(elevation.max(0.))
+    }
+}
+
+/// Planet point under a world column.
+pub fn planet_point(column: IVec2) -> [f64; 3] {
+    [
+        column.x as f64 / BLOCKS_PER_UNIT,
+        column.y as f64 / BLOCKS_PER_UNIT,
+        0.,
+    ]
+}
+
+/// Planet elevation under each world column.
+pub fn elevations(columns: &[IVec2]) -> Vec<f64> {
+    let points: Vec<[f64; 3]> = columns.iter().map(|column| planet_point(*column)).collect();
+
+    sample_points(&points)
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::Shape;
+use crate::orientation::Orientation;
+use crate::state::StateProperties;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    pub const AIR: BlockId = BlockId(0);
+}
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub name: String,
+    /// Stops entities moving through it.
+    pub solid: bool,
+    /// Hides the faces of its neighbours and stops light.
+    pub opaque: bool,
+    /// Water and the like, that mobs keep out of.
+    pub liquid: bool,
+    /// Block light level emitted, from 0 to 15.
+    pub emission: u8,
+    pub color: [f32; 3],
+    /// Color of each model face, indexed like `Orientation::ALL`.
+    pub faces: [[f32; 3]; 6],
+    pub states: StateProperties,
+    pub shape: Shape,
+    /// Most items of this block a single inventory slot holds.
+    pub max_stack: u8,
+}
+
+impl Block {
+    pub fn new(name: &str, color: [f32; 3]) -> Self {
+        Self {
+            name: name.to_string(),
+            solid: true,
+            opaque: true,
+            liquid: false,
+            emission: 0,
+            color,
+            faces: [color; 6],
+            states: StateProperties::default(),
+            shape: Shape::Cube,
+            max_stack: 64,
+        }
+    }
+
+    pub fn with_face(mut self, face: Orientation, color: [f32; 3]) -> Self {
+        self.faces[face.index()] = color;
+        self
+    }
+
+    pub fn face_color(&self, face: Orientation) -> [f32; 3] {
+        self.faces[face.index()]
+    }
+}
+
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+}
+
+impl BlockRegistry {
+    pub fn empty() -> Self {
+        Self {
+            blocks: vec![Block {
+                solid: false,
+                opaque: false,
+                ..Block::new("air", [0.; 3])
+            }],
+        }
+    }
+
+    pub fn register(&mut self, block: Block) -> BlockId {
+        if let Some(id) = self.id(&block.name) {
+            self.blocks[id.0 as usize] = block;
+            return id;
+        }
+
+        self.blocks.push(block);
+
+        BlockId(self.blocks.len() as u16 - 1)
+    }
+
+    /// Unknown ids are treated as air.
+    pub fn get(&self, id: BlockId) -> &Block {
+        self.blocks.get(id.0 as usize).unwrap_or(&self.blocks[0])
+    }
+
+    pub fn id(&self, name: &str) -> Option<BlockId> {
+        self.blocks
+            .iter()
+            .position(|block| block.name == name)
+            .map(|index| BlockId(index as u16))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &Block)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (BlockId(index as u16), block))
+    }
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register(Block::new("grass", [0.3, 0.7, 0.2]));
+        registry.register(Block::new("dirt", [0.45, 0.3, 0.15]));
+        registry.register(Block::new("stone", [0.5, 0.5, 0.5]));
+        registry.register(Block {
+            solid: false,
+            opaque: false,
+            emission: 14,
+            shape: Shape::torch(),
+            ..Block::new("torch", [1., 0.85, 0.4])
+        });
+        registry.register(Block {
+            states: StateProperties {
+                axis: true,
+                ..Default::default()
+            },
+            ..Block::new("log", [0.4, 0.28, 0.15])
+                .with_face(Orientation::Top, [0.7, 0.6, 0.4])
+                .with_face(Orientation::Bottom, [0.7, 0.6, 0.4])
+        });
+        registry.register(Block {
+            states: StateProperties {
+                facing: true,
+                ..Default::default()
+            },
+            ..Block::new("furnace", [0.45, 0.45, 0.45])
+                .with_face(Orientation::Front, [0.2, 0.2, 0.2])
+        });
+        registry.register(Block {
+            opaque: false,
+            states: StateProperties {
+                facing: true,
+                half: true,
+                ..Default::default()
+            },
+            shape: Shape::stairs(),
+            ..Block::new("stairs", [0.7, 0.55, 0.35])
+                .with_face(Orientation::Front, [0.6, 0.45, 0.28])
+        });
+        registry.register(Block {
+            opaque: false,
+            states: StateProperties {
+                half: true,
+                ..Default::default()
+            },
+            shape: Shape::slab(),
+            ..Block::new("slab", [0.55, 0.55, 0.55])
+        });
+        registry.register(Block {
+            opaque: false,
+            shape: Shape::Fence,
+            ..Block::new("fence", [0.7, 0.55, 0.35])
+        });
+        registry.register(Block {
+            solid: false,
+            opaque: false,
+            shape: Shape::Cross,
+            ..Block::new("tall_grass", [0.35, 0.75, 0.25])
+        });
+        registry.register(Block {
+            solid: false,
+            opaque: false,
+            shape: Shape::Cross,
+            ..Block::new("flower", [0.9, 0.25, 0.3])
+        });
+        registry.register(Block::new("planks", [0.75, 0.6, 0.38]));
+        registry.register(Block {
+            solid: false,
+            opaque: false,
+            liquid: true,
+            ..Block::new("water", [0.2, 0.4, 0.8])
+        });
+
+        registry
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_math::{IVec2, IVec3};
+
+use crate::block::{BlockId, BlockRegistry};
+use crate::coords::{BlockPos, LocalPos};
+use crate::light::{self, LightWorld};
+use crate::path::PathWorld;
+use crate::state::BlockState;
+
+pub const CHUNK_SIZE: i32 = 16;
+pub const CHUNK_HEIGHT: i32 = 130;
+
+const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_HEIGHT) as usize;
+
+/// Sky light in the high nibble, block light in the low one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Light(pub u8);
+
+impl Light {
+    pub fn new(sky: u8, block: u8) -> Self {
+        Self((sky << 4) | (block & 0xf))
+    }
+
+    pub fn sky(&self) -> u8 {
+        self.0 >> 4
+    }
+
+    pub fn block(&self) -> u8 {
+        self.0 & 0xf
+    }
+
+    pub fn brightest(&self) -> u8 {
+        self.sky().max(self.block())
+    }
+}
+
+/// Blocks and light of a 16 x 130 x 16 column, indexed by local position.
+#[derive(Clone)]
+pub struct ChunkData {
+    blocks: Vec<BlockId>,
+    states: Vec<BlockState>,
+    light: Vec<Light>,
+}
+
+impl Default for ChunkData {
+    fn default() -> Self {
+        Self {
+            blocks: vec![BlockId::AIR; CHUNK_VOLUME],
+            states: vec![BlockState::default(); CHUNK_VOLUME],
+            light: vec![Light::default(); CHUNK_VOLUME],
+        }
+    }
+}
+
+impl ChunkData {
+    pub fn contains(local: IVec3) -> bool {
+        LocalPos::new(local).is_some()
+    }
+
+    fn index(local: IVec3) -> usize {
+        ((local.y * CHUNK_SIZE + local.z) * CHUNK_SIZE + local.x) as usize
+    }
+
+    pub fn block(&self, local: IVec3) -> BlockId {
+        self.blocks[Self::index(local)]
+    }
+
+    pub fn set_block(&mut self, local: IVec3, block: BlockId) {
+        self.blocks[Self::index(local)] = block;
+    }
+
+    pub fn state(&self, local: IVec3) -> BlockState {
+        self.states[Self::index(local)]
+    }
+
+    pub fn set_state(&mut self, local: IVec3, state: BlockState) {
+        self.states[Self::index(local)] = state;
+    }
+
+    pub fn light(&self, local: IVec3) -> Light {
+        self.light[Self::index(local)]
+    }
+
+    pub fn set_light(&mut self, local: IVec3, light: Light) {
+        self.light[Self::index(local)] = light;
+    }
+}
+
+/// Every loaded chunk, addressed with world block positions.
+///
+/// Block `(x, y, z)` fills the unit cube `[x, x + 1) x [y, y + 1) x [z, z + 1)`,
+/// see `BlockPos` for the chunk it belongs to.
+#[derive(Default)]
+pub struct VoxelStore {
+    pub registry: BlockRegistry,
+    chunks: HashMap<IVec2, ChunkData>,
+    /// Chunks whose blocks were changed since they were generated.
+    edited: HashSet<IVec2>,
+}
+
+impl VoxelStore {
+    pub fn new(registry: BlockRegistry) -> Self {
+        Self {
+            registry,
+            chunks: HashMap::new(),
+            edited: HashSet::new(),
+        }
+    }
+
+    /// Adds a freshly generated chunk and lights it.
+    pub fn insert(&mut self, coord: IVec2, chunk: ChunkData) {
+        self.chunks.insert(coord, chunk);
+        self.edited.remove(&coord);
+        light::light_chunk(self, coord);
+    }
+
+    pub fn chunk(&self, coord: IVec2) -> Option<&ChunkData> {
+        self.chunks.get(&coord)
+    }
+
+    /// Unloads a chunk, returns its blocks if it was loaded.
+    pub fn remove(&mut self, coord: IVec2) -> Option<ChunkData> {
+        self.edited.remove(&coord);
+        self.chunks.remove(&coord)
+    }
+
+    /// Whether a block of the chunk was replaced since it was generated.
+    pub fn is_edited(&self, coord: IVec2) -> bool {
+        self.edited.contains(&coord)
+    }
+
+    /// Unloads every chunk, the registry is kept.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.edited.clear();
+    }
+
+    /// Coordinates of the loaded chunks, in no particular order.
+    pub fn coords(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// Chunks loaded.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn is_loaded(&self, coord: IVec2) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    /// `None` for unloaded chunks and outside the world height.
+    pub fn block(&self, position: IVec3) -> Option<BlockId> {
+        let position = BlockPos(position);
+        let local = position.local()?;
+
+        self.chunks
+            .get(&position.chunk().0)
+            .map(|chunk| chunk.block(local.get()))
+    }
+
+    /// Default state for unloaded chunks and outside the world height.
+    pub fn state(&self, position: IVec3) -> BlockState {
+        let position = BlockPos(position);
+
+        match (position.local(), self.chunks.get(&position.chunk().0)) {
+            (Some(local), Some(chunk)) => chunk.state(local.get()),
+            _ => BlockState::default(),
+        }
+    }
+
+    /// Unloaded chunks and blocks outside the world height count as air.
+    pub fn is_solid(&self, position: IVec3) -> bool {
+        self.block(position)
+            .map_or(false, |block| self.registry.get(block).solid)
+    }
+
+    /// Highest solid block of a loaded column.
+    pub fn surface(&self, column: IVec2) -> Option<i32> {
+        (0..CHUNK_HEIGHT)
+            .rev()
+            .find(|y| self.is_solid(IVec3::new(column.x, *y, column.y)))
+    }
+
+    /// Changes a block and updates the light around it, returns the previous
+    /// block or `None` if the position is not loaded.
+    pub fn replace(&mut self, position: IVec3, block: BlockId) -> Option<BlockId> {
+        self.replace_state(position, block, BlockState::default())
+    }
+
+    /// `replace` with a block state other than the default one.
+    pub fn replace_state(
+        &mut self,
+        position: IVec3,
+        block: BlockId,
+        state: BlockState,
+    ) -> Option<BlockId> {
+        let block_pos = BlockPos(position);
+        let local = block_pos.local()?.get();
+
+        let chunk = self.chunks.get_mut(&block_pos.chunk().0)?;
+        let old = chunk.block(local);
+        chunk.set_block(local, block);
+        chunk.set_state(local, state);
+        self.edited.insert(block_pos.chunk().0);
+
+        light::block_changed(self, position);
+
+        Some(old)
+    }
+}
+
+impl PathWorld for VoxelStore {
+    fn solid(&self, position: IVec3) -> Option<bool> {
+        if position.y >= CHUNK_HEIGHT {
+            return Some(false);
+        }
+
+        self.block(position)
+            .map(|block| self.registry.get(block).solid)
+    }
+
+    fn liquid(&self, position: IVec3) -> bool {
+        self.block(position)
+            .map_or(false, |block| self.registry.get(block).liquid)
+    }
+}
+
+impl LightWorld for VoxelStore {
+    fn opaque(&self, position: IVec3) -> Option<bool> {
+        self.block(position)
+            .map(|block| self.registry.get(block).opaque)
+    }
+
+    fn emission(&self, position: IVec3) -> u8 {
+        self.block(position)
+            .map_or(0, |block| self.registry.get(block).emission)
+    }
+
+    fn light(&self, position: IVec3) -> Light {
+        if position.y >= CHUNK_HEIGHT {
+            return Light::new(light::MAX_LIGHT, 0);
+        }
+
+        let position = BlockPos(position);
+
+        match (position.local(), self.chunks.get(&position.chunk().0)) {
+            (Some(local), Some(chunk)) => chunk.light(local.get()),
+            _ => Light::default(),
+        }
+    }
+
+    fn set_light(&mut self, position: IVec3, light: Light) {
+        let position = BlockPos(position);
+
+        if let (Some(local), Some(chunk)) =
+            (position.local(), self.chunks.get_mut(&position.chunk().0))
+        {
+            chunk.set_light(local.get(), light);
+        }
+    }
+}
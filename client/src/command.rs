@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+const TELEPORT_USAGE: &str = "/tp <x> <y> <z>";
+const SEED_USAGE: &str = "/seed";
+const TIME_USAGE: &str = "/time set <day|noon|night|midnight|0..1>";
+const GIVE_USAGE: &str = "/give <block> [count]";
+const GAMEMODE_USAGE: &str = "/gamemode <survival|creative|spectator>";
+const BIND_USAGE: &str = "/bind <action>";
+const SET_USAGE: &str = "/set <setting> <value>";
+
+/// Where a command runs when playing on a server.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    /// Only reads or changes this game.
+    Client,
+    /// Changes the world, the server runs it when there is one.
+    Server,
+}
+
+/// Coordinate of `/tp`, `~` makes it relative to the current one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Coordinate {
+    Absolute(f64),
+    Relative(f64),
+}
+
+impl Coordinate {
+    pub fn resolve(&self, current: f64) -> f64 {
+        match self {
+            Coordinate::Absolute(value) => *value,
+            Coordinate::Relative(offset) => current + offset,
+        }
+    }
+}
+
+impl std::str::FromStr for Coordinate {
+    type Err = CommandError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || CommandError::InvalidArgument(text.to_string());
+
+        match text.strip_prefix('~') {
+            Some("") => Ok(Coordinate::Relative(0.)),
+            Some(offset) => offset
+                .parse()
+                .map(Coordinate::Relative)
+                .map_err(|_| invalid()),
+            None => text
+                .parse()
+                .map(Coordinate::Absolute)
+                .map_err(|_| invalid()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameMode {
+    Survival,
+    /// Placed blocks are not taken from the inventory.
+    Creative,
+    /// Flies through blocks.
+    Spectator,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::Survival
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Teleport([Coordinate; 3]),
+    Seed,
+    /// Fraction of the day, as in `WorldTime`.
+    SetTime(f32),
+    /// Block named as in the registry.
+    Give {
+        block: String,
+        count: u8,
+    },
+    GameMode(GameMode),
+    /// Action named as in the settings file, bound to the next key pressed.
+    Bind(String),
+    /// Setting named as in the settings file, with its new value.
+    Set {
+        name: String,
+        value: String,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CommandError {
+    UnknownCommand(String),
+    /// Wrong number of arguments, with the expected ones.
+    Usage(&'static str),
+    InvalidArgument(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(name) => write!(f, "unknown command /{}", name),
+            Self::Usage(usage) => write!(f, "usage: {}", usage),
+            Self::InvalidArgument(argument) => write!(f, "invalid argument {}", argument),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub side: Side,
+    /// Builds the command from its arguments, `/` and name left out.
+    pub parse: fn(&[&str]) -> Result<Command, CommandError>,
+}
+
+/// What to do with a line typed in the console.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Dispatch {
+    /// Runs in this game.
+    Local(Command),
+    /// Sent as typed to the server, which runs it.
+    Forward(String),
+    Chat(String),
+}
+
+/// Every command the console knows, by name.
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, CommandSpec>,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            commands: BTreeMap::new(),
+        };
+
+        registry.register(CommandSpec {
+            name: "tp",
+            usage: TELEPORT_USAGE,
+            side: Side::Server,
+            parse: parse_teleport,
+        });
+        registry.register(CommandSpec {
+            name: "seed",
+            usage: SEED_USAGE,
+            side: Side::Client,
+            parse: |arguments| match arguments {
+                [] => Ok(Command::Seed),
+                _ => Err(CommandError::Usage(SEED_USAGE)),
+            },
+        });
+        registry.register(CommandSpec {
+            name: "time",
+            usage: TIME_USAGE,
+            side: Side::Server,
+            parse: parse_time,
+        });
+        registry.register(CommandSpec {
+            name: "give",
+            usage: GIVE_USAGE,
+            side: Side::Server,
+            parse: parse_give,
+        });
+        registry.register(CommandSpec {
+            name: "gamemode",
+            usage: GAMEMODE_USAGE,
+            side: Side::Server,
+            parse: parse_gamemode,
+        });
+        registry.register(CommandSpec {
+            name: "bind",
+            usage: BIND_USAGE,
+            side: Side::Client,
+            parse: |arguments| match arguments {
+                [action] => Ok(Command::Bind(action.to_string())),
+                _ => Err(CommandError::Usage(BIND_USAGE)),
+            },
+        });
+        registry.register(CommandSpec {
+            name: "set",
+            usage: SET_USAGE,
+            side: Side::Client,
+            parse: |arguments| match arguments {
+                [name, value] => Ok(Command::Set {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                }),
+                _ => Err(CommandError::Usage(SET_USAGE)),
+            },
+        });
+
+        registry
+    }
+}
+
+impl CommandRegistry {
+    /// Adds a command, replacing the one of the same name.
+    pub fn register(&mut self, spec: CommandSpec) {
+        self.commands.insert(spec.name, spec);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CommandSpec> {
+        self.commands.get(name)
+    }
+
+    /// Sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = &CommandSpec> {
+        self.commands.values()
+    }
+
+    /// Parses a command line, with or without its leading `/`.
+    pub fn parse(&self, line: &str) -> Result<(Command, Side), CommandError> {
+        let mut words = line.trim().trim_start_matches('/').split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let arguments: Vec<&str> = words.collect();
+
+        let spec = self
+            .get(name)
+            .ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+
+        Ok(((spec.parse)(&arguments)?, spec.side))
+    }
+
+    /// Chat for lines not starting with `/`. Commands run here unless they
+    /// belong to a server and the game is `remote`, they are still parsed
+    /// first so typos never leave the game.
+    pub fn dispatch(&self, line: &str, remote: bool) -> Result<Dispatch, CommandError> {
+        let line = line.trim();
+
+        if !line.starts_with('/') {
+            return Ok(Dispatch::Chat(line.to_string()));
+        }
+
+        match self.parse(line)? {
+            (_, Side::Server) if remote => Ok(Dispatch::Forward(line.to_string())),
+            (command, _) => Ok(Dispatch::Local(command)),
+        }
+    }
+}
+
+fn parse_teleport(arguments: &[&str]) -> Result<Command, CommandError> {
+    match arguments {
+        [x, y, z] => Ok(Command::Teleport([x.parse()?, y.parse()?, z.parse()?])),
+        _ => Err(CommandError::Usage(TELEPORT_USAGE)),
+    }
+}
+
+fn parse_time(arguments: &[&str]) -> Result<Command, CommandError> {
+    let value = match arguments {
+        ["set", value] => *value,
+        _ => return Err(CommandError::Usage(TIME_USAGE)),
+    };
+
+    let time = match value {
+        "day" => 0.3,
+        "noon" => 0.5,
+        "night" => 0.8,
+        "midnight" => 0.,
+        _ => value
+            .parse()
+            .ok()
+            .filter(|time| (0. ..1.).contains(time))
+            .ok_or_else(|| CommandError::InvalidArgument(value.to_string()))?,
+    };
+
+    Ok(Command::SetTime(time))
+}
+
+fn parse_give(arguments: &[&str]) -> Result<Command, CommandError> {
+    let (block, count) = match arguments {
+        [block] => (block, 1),
+        [block, count] => (
+            block,
+            count
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| CommandError::InvalidArgument(count.to_string()))?,
+        ),
+        _ => return Err(CommandError::Usage(GIVE_USAGE)),
+    };
+
+    Ok(Command::Give {
+        block: block.to_string(),
+        count,
+    })
+}
+
+fn parse_gamemode(arguments: &[&str]) -> Result<Command, CommandError> {
+    let mode = match arguments {
+        ["survival"] => GameMode::Survival,
+        ["creative"] => GameMode::Creative,
+        ["spectator"] => GameMode::Spectator,
+        [mode] => return Err(CommandError::InvalidArgument(mode.to_string())),
+        _ => return Err(CommandError::Usage(GAMEMODE_USAGE)),
+    };
+
+    Ok(Command::GameMode(mode))
+}
@@ -0,0 +1,145 @@
+use bevy_math::{IVec2, IVec3, Vec3};
+
+use crate::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::orientation::Orientation;
+
+/// World position of a block, filling the unit cube from `(x, y, z)` to
+/// `(x + 1, y + 1, z + 1)`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BlockPos(pub IVec3);
+
+/// Column of 16 x 16 blocks, `(x, z)` in chunk units.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ChunkPos(pub IVec2);
+
+/// Position of a block inside its chunk, always within `16 x 130 x 16`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct LocalPos(IVec3);
+
+impl BlockPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self(IVec3::new(x, y, z))
+    }
+
+    /// Block containing a world point, flooring toward negative infinity.
+    pub fn from_world(position: Vec3) -> Self {
+        Self(position.floor().as_ivec3())
+    }
+
+    /// Minimum corner of the block.
+    pub fn as_vec3(&self) -> Vec3 {
+        self.0.as_vec3()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.as_vec3() + Vec3::splat(0.5)
+    }
+
+    pub fn chunk(&self) -> ChunkPos {
+        ChunkPos(IVec2::new(
+            self.0.x.div_euclid(CHUNK_SIZE),
+            self.0.z.div_euclid(CHUNK_SIZE),
+        ))
+    }
+
+    /// `None` above or below the world height.
+    pub fn local(&self) -> Option<LocalPos> {
+        LocalPos::new(IVec3::new(
+            self.0.x.rem_euclid(CHUNK_SIZE),
+            self.0.y,
+            self.0.z.rem_euclid(CHUNK_SIZE),
+        ))
+    }
+
+    pub fn from_parts(chunk: ChunkPos, local: LocalPos) -> Self {
+        chunk.origin().offset(local.0)
+    }
+
+    pub fn offset(&self, offset: IVec3) -> Self {
+        Self(self.0 + offset)
+    }
+
+    /// The six blocks sharing a face with this one.
+    pub fn neighbours(self) -> impl Iterator<Item = BlockPos> {
+        Orientation::iter().map(move |face| BlockPos(face.neighbour(self.0)))
+    }
+}
+
+impl From<IVec3> for BlockPos {
+    fn from(position: IVec3) -> Self {
+        Self(position)
+    }
+}
+
+impl From<BlockPos> for IVec3 {
+    fn from(position: BlockPos) -> Self {
+        position.0
+    }
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, z: i32) -> Self {
+        Self(IVec2::new(x, z))
+    }
+
+    /// Chunk containing a world point.
+    pub fn from_world(position: Vec3) -> Self {
+        BlockPos::from_world(position).chunk()
+    }
+
+    /// Lowest block of the chunk.
+    pub fn origin(&self) -> BlockPos {
+        BlockPos::new(self.0.x * CHUNK_SIZE, 0, self.0.y * CHUNK_SIZE)
+    }
+
+    /// Distance in chunks along the furthest axis.
+    pub fn distance(&self, other: ChunkPos) -> i32 {
+        (self.0 - other.0).abs().max_element()
+    }
+
+    /// The four chunks sharing a side with this one.
+    pub fn neighbours(self) -> impl Iterator<Item = ChunkPos> {
+        [IVec2::NEG_X, IVec2::X, IVec2::NEG_Y, IVec2::Y]
+            .into_iter()
+            .map(move |side| ChunkPos(self.0 + side))
+    }
+
+    /// Chunks within `radius` along both axes, this one included.
+    pub fn around(self, radius: i32) -> impl Iterator<Item = ChunkPos> {
+        (-radius..=radius)
+            .flat_map(move |x| (-radius..=radius).map(move |z| ChunkPos(self.0 + IVec2::new(x, z))))
+    }
+}
+
+impl From<IVec2> for ChunkPos {
+    fn from(position: IVec2) -> Self {
+        Self(position)
+    }
+}
+
+impl From<ChunkPos> for IVec2 {
+    fn from(position: ChunkPos) -> Self {
+        position.0
+    }
+}
+
+impl LocalPos {
+    /// `None` outside the chunk.
+    pub fn new(local: IVec3) -> Option<Self> {
+        let inside = (0..CHUNK_SIZE).contains(&local.x)
+            && (0..CHUNK_HEIGHT).contains(&local.y)
+            && (0..CHUNK_SIZE).contains(&local.z);
+
+        inside.then_some(Self(local))
+    }
+
+    pub fn get(&self) -> IVec3 {
+        self.0
+    }
+}
+
+impl From<LocalPos> for IVec3 {
+    fn from(position: LocalPos) -> Self {
+        position.0
+    }
+}
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::block::{BlockId, BlockRegistry};
+use crate::inventory::ItemStack;
+
+/// Recipe asset, blocks named as in the registry.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RecipeDefinition {
+    /// Rows of the pattern, each character a key or a space for an empty cell.
+    Shaped {
+        pattern: Vec<String>,
+        key: HashMap<char, String>,
+        result: String,
+        #[serde(default = "one")]
+        count: u8,
+    },
+    Shapeless {
+        ingredients: Vec<String>,
+        result: String,
+        #[serde(default = "one")]
+        count: u8,
+    },
+}
+
+fn one() -> u8 {
+    1
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RecipeError {
+    UnknownBlock(String),
+    UnknownKey(char),
+    /// No ingredient at all.
+    Empty,
+    /// Pattern rows of different lengths.
+    Ragged,
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownBlock(name) => write!(f, "unknown block {}", name),
+            Self::UnknownKey(key) => write!(f, "unknown key {:?}", key),
+            Self::Empty => write!(f, "no ingredient"),
+            Self::Ragged => write!(f, "pattern rows differ in length"),
+        }
+    }
+}
+
+impl Error for RecipeError {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Ingredients {
+    /// Cells row by row, trimmed to the ingredients.
+    Shaped {
+        width: usize,
+        cells: Vec<Option<BlockId>>,
+    },
+    /// Sorted, so two lists holding the same blocks compare equal.
+    Shapeless(Vec<BlockId>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Recipe {
+    pub ingredients: Ingredients,
+    pub result: ItemStack,
+}
+
+impl RecipeDefinition {
+    pub fn recipe(&self, registry: &BlockRegistry) -> Result<Recipe, RecipeError> {
+        let block = |name: &String| {
+            registry
+                .id(name)
+                .ok_or_else(|| RecipeError::UnknownBlock(name.clone()))
+        };
+
+        let (ingredients, result, count) = match self {
+            RecipeDefinition::Shaped {
+                pattern,
+                key,
+                result,
+                count,
+            } => {
+                let width = pattern.first().map_or(0, |row| row.chars().count());
+
+                if pattern.iter().any(|row| row.chars().count() != width) {
+                    return Err(RecipeError::Ragged);
+                }
+
+                let mut cells = Vec::new();
+
+                for cell in pattern.iter().flat_map(|row| row.chars()) {
+                    if cell == ' ' {
+                        cells.push(None);
+                        continue;
+                    }
+
+                    let name = key.get(&cell).ok_or(RecipeError::UnknownKey(cell))?;
+                    cells.push(Some(block(name)?));
+                }
+
+                (
+                    trim(&cells, width).ok_or(RecipeError::Empty)?,
+                    result,
+                    count,
+                )
+            }
+            RecipeDefinition::Shapeless {
+                ingredients,
+                result,
+                count,
+            } => {
+                let mut blocks = ingredients
+                    .iter()
+                    .map(block)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if blocks.is_empty() {
+                    return Err(RecipeError::Empty);
+                }
+
+                blocks.sort_by_key(|block| block.0);
+
+                (Ingredients::Shapeless(blocks), result, count)
+            }
+        };
+
+        Ok(Recipe {
+            ingredients,
+            result: ItemStack::new(block(result)?, *count),
+        })
+    }
+}
+
+/// Smallest rectangle holding every filled cell, `None` when all are empty.
+fn trim(cells: &[Option<BlockId>], width: usize) -> Option<Ingredients> {
+    if width == 0 {
+        return None;
+    }
+
+    let filled = |x: usize, y: usize| cells[x + y * width].is_some();
+    let height = cells.len() / width;
+
+    let columns: Vec<usize> = (0..width)
+        .filter(|x| (0..height).any(|y| filled(*x, y)))
+        .collect();
+    let rows: Vec<usize> = (0..height)
+        .filter(|y| (0..width).any(|x| filled(x, *y)))
+        .collect();
+
+    let (left, right) = (*columns.first()?, *columns.last()?);
+    let (top, bottom) = (*rows.first()?, *rows.last()?);
+
+    let cells = (top..=bottom)
+        .flat_map(|y| (left..=right).map(move |x| x + y * width))
+        .map(|index| cells[index])
+        .collect();
+
+    Some(Ingredients::Shaped {
+        width: right - left + 1,
+        cells,
+    })
+}
+
+impl Recipe {
+    /// Whether the blocks laid out on a grid `width` slots wide make this
+    /// recipe, shaped ones anywhere on the grid and mirrored or not.
+    pub fn matches(&self, grid: &[Option<BlockId>], width: usize) -> bool {
+        match &self.ingredients {
+            Ingredients::Shaped {
+                width: pattern,
+                cells,
+            } => {
+                let (width, grid) = match trim(grid, width) {
+                    Some(Ingredients::Shaped { width, cells }) => (width, cells),
+                    _ => return false,
+                };
+
+                if width != *pattern || grid.len() != cells.len() {
+                    return false;
+                }
+
+                let mirrored = grid.chunks(width).flat_map(|row| row.iter().rev()).copied();
+
+                grid == *cells || mirrored.eq(cells.iter().copied())
+            }
+            Ingredients::Shapeless(blocks) => {
+                let mut grid: Vec<BlockId> = grid.iter().flatten().copied().collect();
+                grid.sort_by_key(|block| block.0);
+
+                grid == *blocks
+            }
+        }
+    }
+}
+
+/// Every known recipe, the first matching one wins.
+#[derive(Default)]
+pub struct RecipeBook {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    pub fn add(&mut self, recipe: Recipe) {
+        self.recipes.push(recipe);
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// Recipe made by the stacks laid out on a crafting grid.
+    pub fn find(&self, grid: &[Option<ItemStack>], width: usize) -> Option<&Recipe> {
+        let blocks: Vec<Option<BlockId>> = grid
+            .iter()
+            .map(|slot| slot.map(|stack| stack.block))
+            .collect();
+
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.matches(&blocks, width))
+    }
+
+    /// Crafts once, using up one item of every filled slot.
+    pub fn craft(&self, grid: &mut [Option<ItemStack>], width: usize) -> Option<ItemStack> {
+        let result = self.find(grid, width)?.result;
+
+        for slot in grid.iter_mut() {
+            *slot = slot.and_then(|stack| {
+                (stack.count > 1).then(|| ItemStack::new(stack.block, stack.count - 1))
+            });
+        }
+
+        Some(result)
+    }
+}
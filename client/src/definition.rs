@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::block::Block;
+use crate::model::{Cuboid, Element, ElementFace, Shape};
+use crate::orientation::Orientation;
+use crate::state::StateProperties;
+
+/// Block asset, named after its file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockDefinition {
+    pub solid: bool,
+    pub opaque: bool,
+    pub liquid: bool,
+    pub emission: u8,
+    /// Color of the block where its model is not drawn, like distant terrain.
+    pub color: [f32; 3],
+    /// Name of the model asset.
+    pub model: String,
+    /// Colors the texture references of the model resolve to.
+    pub textures: HashMap<String, [f32; 3]>,
+    /// Multiplies the faces the model marks as tinted.
+    pub tint: [f32; 3],
+    pub states: StateProperties,
+    pub max_stack: u8,
+}
+
+impl Default for BlockDefinition {
+    fn default() -> Self {
+        Self {
+            solid: true,
+            opaque: true,
+            liquid: false,
+            emission: 0,
+            color: [1.; 3],
+            model: "cube".to_string(),
+            textures: HashMap::new(),
+            tint: [1.; 3],
+            states: StateProperties::default(),
+            max_stack: 64,
+        }
+    }
+}
+
+/// Model asset, shared by every block naming it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ModelDefinition {
+    Elements(Vec<ElementDefinition>),
+    Cross {
+        texture: String,
+        #[serde(default)]
+        tint: bool,
+    },
+    Fence {
+        texture: String,
+        #[serde(default)]
+        tint: bool,
+    },
+}
+
+/// Box of a model, in sixteenths of a block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElementDefinition {
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+    /// Faces left out are not drawn.
+    pub faces: HashMap<Orientation, FaceDefinition>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FaceDefinition {
+    /// Key into the textures of the block.
+    pub texture: String,
+    /// Block side whose neighbour hides the face.
+    #[serde(default)]
+    pub cullface: Option<Orientation>,
+    #[serde(default)]
+    pub tint: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DefinitionError {
+    UnknownModel(String),
+    UnknownTexture(String),
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownModel(name) => write!(f, "unknown model {}", name),
+            Self::UnknownTexture(name) => write!(f, "unknown texture {}", name),
+        }
+    }
+}
+
+impl Error for DefinitionError {}
+
+impl BlockDefinition {
+    fn texture(&self, name: &str, tinted: bool) -> Result<[f32; 3], DefinitionError> {
+        let color = self
+            .textures
+            .get(name)
+            .ok_or_else(|| DefinitionError::UnknownTexture(name.to_string()))?;
+
+        if !tinted {
+            return Ok(*color);
+        }
+
+        Ok([0, 1, 2].map(|channel| color[channel] * self.tint[channel]))
+    }
+
+    /// Block described by this definition, its model taken from `models`.
+    pub fn block(
+        &self,
+        name: &str,
+        models: &HashMap<String, ModelDefinition>,
+    ) -> Result<Block, DefinitionError> {
+        let model = models
+            .get(&self.model)
+            .ok_or_else(|| DefinitionError::UnknownModel(self.model.clone()))?;
+
+        let mut block = Block {
+            solid: self.solid,
+            opaque: self.opaque,
+            liquid: self.liquid,
+            emission: self.emission,
+            states: self.states,
+            max_stack: self.max_stack,
+            ..Block::new(name, self.color)
+        };
+
+        match model {
+            ModelDefinition::Cross { texture, tint } => {
+                block.color = self.texture(texture, *tint)?;
+                block.shape = Shape::Cross;
+            }
+            ModelDefinition::Fence { texture, tint } => {
+                block.faces = [self.texture(texture, *tint)?; 6];
+                block.shape = Shape::Fence;
+            }
+            ModelDefinition::Elements(definitions) => {
+                let mut elements = Vec::new();
+
+                for definition in definitions {
+                    let cuboid = Cuboid::pixels(definition.from, definition.to);
+                    let mut faces = [None; 6];
+
+                    for (face, face_definition) in definition.faces.iter() {
+                        faces[face.index()] = Some(ElementFace {
+                            color: Some(
+                                self.texture(&face_definition.texture, face_definition.tint)?,
+                            ),
+                            cull: face_definition.cullface,
+                        });
+                    }
+
+                    elements.push(Element { cuboid, faces });
+                }
+
+                // a plain cube goes through the greedy mesher with its face colors
+                match elements.as_slice() {
+                    [element] if element.is_cube() => {
+                        for face in Orientation::iter() {
+                            if let Some(ElementFace {
+                                color: Some(color), ..
+                            }) = element.faces[face.index()]
+                            {
+                                block.faces[face.index()] = color;
+                            }
+                        }
+                    }
+                    _ => block.shape = Shape::Boxes(elements),
+                }
+            }
+        }
+
+        Ok(block)
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::block::{BlockId, BlockRegistry};
+
+/// Slots selectable from the hotbar, the first ones of the inventory.
+pub const HOTBAR_SIZE: usize = 9;
+
+/// Hotbar plus three rows of storage.
+pub const INVENTORY_SIZE: usize = 36;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub block: BlockId,
+    /// Never zero, an empty slot holds no stack.
+    pub count: u8,
+}
+
+impl ItemStack {
+    pub fn new(block: BlockId, count: u8) -> Self {
+        Self { block, count }
+    }
+}
+
+/// Player inventory, the same layout in save files and over the network.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    /// Hotbar slot in hand.
+    selected: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(INVENTORY_SIZE)
+    }
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+            selected: 0,
+        }
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn get(&self, slot: usize) -> Option<ItemStack> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < self.hotbar_size() {
+            self.selected = slot;
+        }
+    }
+
+    /// Moves the selection by `delta` slots, wrapping around the hotbar.
+    pub fn scroll(&mut self, delta: i32) {
+        let size = self.hotbar_size() as i32;
+
+        if size > 0 {
+            self.selected = (self.selected as i32 + delta).rem_euclid(size) as usize;
+        }
+    }
+
+    fn hotbar_size(&self) -> usize {
+        self.slots.len().min(HOTBAR_SIZE)
+    }
+
+    /// Stack in the selected hotbar slot.
+    pub fn held(&self) -> Option<ItemStack> {
+        self.get(self.selected)
+    }
+
+    /// Whether one more of `block` fits, on a stack of it or in an empty slot.
+    pub fn has_room(&self, block: BlockId, registry: &BlockRegistry) -> bool {
+        let max = registry.get(block).max_stack.max(1);
+
+        self.slots.iter().any(|slot| match slot {
+            Some(stack) => stack.block == block && stack.count < max,
+            None => true,
+        })
+    }
+
+    /// Adds a stack, topping up the stacks of the same block first then filling
+    /// empty slots in order. Returns what did not fit.
+    pub fn insert(&mut self, stack: ItemStack, registry: &BlockRegistry) -> u8 {
+        let max = registry.get(stack.block).max_stack.max(1);
+        let mut left = stack.count;
+
+        for slot in self.slots.iter_mut().flatten() {
+            if left == 0 {
+                break;
+            }
+
+            if slot.block == stack.block && slot.count < max {
+                let moved = left.min(max - slot.count);
+                slot.count += moved;
+                left -= moved;
+            }
+        }
+
+        for slot in self.slots.iter_mut() {
+            if left == 0 {
+                break;
+            }
+
+            if slot.is_none() {
+                let moved = left.min(max);
+                *slot = Some(ItemStack::new(stack.block, moved));
+                left -= moved;
+            }
+        }
+
+        left
+    }
+
+    /// Takes up to `count` items out of a slot.
+    pub fn remove(&mut self, slot: usize, count: u8) -> Option<ItemStack> {
+        let stack = self.slots.get_mut(slot)?;
+        let held = (*stack)?;
+        let taken = count.min(held.count);
+
+        if taken == 0 {
+            return None;
+        }
+
+        *stack = (held.count > taken).then(|| ItemStack::new(held.block, held.count - taken));
+
+        Some(ItemStack::new(held.block, taken))
+    }
+
+    /// Takes the larger half of a stack out of its slot.
+    pub fn split(&mut self, slot: usize) -> Option<ItemStack> {
+        let count = self.get(slot)?.count;
+
+        self.remove(slot, count - count / 2)
+    }
+
+    /// Moves as many items as fit from one slot onto the stack of the same
+    /// block in another, swapping the slots when they hold different blocks.
+    pub fn merge(&mut self, from: usize, to: usize, registry: &BlockRegistry) {
+        if from == to || from >= self.slots.len() || to >= self.slots.len() {
+            return;
+        }
+
+        match (self.slots[from], self.slots[to]) {
+            (Some(source), Some(target)) if source.block == target.block => {
+                let max = registry.get(target.block).max_stack.max(1);
+                let moved = source.count.min(max.saturating_sub(target.count));
+
+                self.slots[to] = Some(ItemStack::new(target.block, target.count + moved));
+                self.slots[from] = (source.count > moved)
+                    .then(|| ItemStack::new(source.block, source.count - moved));
+            }
+            _ => self.slots.swap(from, to),
+        }
+    }
+}
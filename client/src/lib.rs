@@ -1,6 +1,23 @@
+pub mod biome;
+pub mod block;
+pub mod chunk;
+pub mod command;
+pub mod coords;
+pub mod crafting;
+pub mod definition;
+pub mod inventory;
+pub mod light;
+pub mod lod;
 pub mod mesh;
+pub mod model;
 pub mod noisemap;
 pub mod orientation;
+pub mod origin;
+pub mod path;
+pub mod physics;
+pub mod state;
+pub mod time;
+pub mod visibility;
 
 #[cfg(test)]
 mod goatland {
@@ -9,7 +26,10 @@ mod goatland {
     use bevy_math::{Quat, Vec2, Vec3};
     use block_mesh::MergeVoxel;
 
-    use crate::{mesh, noisemap, orientation};
+    use crate::{
+        biome, block, chunk, command, coords, crafting, definition, inventory, light::LightWorld,
+        lod, mesh, model, noisemap, orientation, origin, path, physics, state, time, visibility,
+    };
 
     #[test]
     fn test_deg2rand() {
@@ -84,34 +104,75 @@ mod goatland {
     fn test_parse_normal() {
         assert_eq!(
             orientation::parse_normal(0, None),
-            orientation::Orientation::Left
+            Ok(orientation::Orientation::Left)
         );
         assert_eq!(
             orientation::parse_normal(1, None),
-            orientation::Orientation::Bottom
+            Ok(orientation::Orientation::Bottom)
         );
         assert_eq!(
             orientation::parse_normal(2, None),
-            orientation::Orientation::Back
+            Ok(orientation::Orientation::Back)
         );
         assert_eq!(
             orientation::parse_normal(3, None),
-            orientation::Orientation::Right
+            Ok(orientation::Orientation::Right)
         );
         assert_eq!(
             orientation::parse_normal(4, None),
-            orientation::Orientation::Top
+            Ok(orientation::Orientation::Top)
         );
         assert_eq!(
             orientation::parse_normal(5, None),
-            orientation::Orientation::Front
+            Ok(orientation::Orientation::Front)
         );
     }
 
     #[test]
-    #[should_panic]
     fn test_invalid_normal() {
-        orientation::parse_normal(0, Some([-1, 1, 0]));
+        assert_eq!(
+            orientation::parse_normal(0, Some([-1, 1, 0])),
+            Err(orientation::OrientationError::InvalidNormal([-1, 1, 0]))
+        );
+        assert_eq!(
+            orientation::parse_normal(6, None),
+            Err(orientation::OrientationError::InvalidFace(6))
+        );
+    }
+
+    #[test]
+    fn test_orientation_faces() {
+        use block_mesh::{QuadCoordinateConfig, RIGHT_HANDED_Y_UP_CONFIG};
+        use orientation::Orientation;
+        use std::convert::TryFrom;
+
+        let mut faces = RIGHT_HANDED_Y_UP_CONFIG.faces;
+        faces.reverse();
+
+        let reversed = QuadCoordinateConfig {
+            faces,
+            ..RIGHT_HANDED_Y_UP_CONFIG
+        };
+
+        assert_eq!(Orientation::iter().count(), 6);
+
+        for face in Orientation::iter() {
+            assert_eq!(face.opposite().opposite(), face);
+            assert_eq!(face.opposite().normal(), -face.normal());
+            assert_eq!(Orientation::try_from(face.normal()), Ok(face));
+            assert_eq!(
+                face.neighbour(bevy_math::IVec3::new(4, -2, 7)) - bevy_math::IVec3::new(4, -2, 7),
+                face.normal()
+            );
+
+            for config in [&RIGHT_HANDED_Y_UP_CONFIG, &reversed] {
+                let index = face.face_index(config).unwrap();
+                assert_eq!(Orientation::from_face_index(config, index), Ok(face));
+            }
+        }
+
+        assert!(Orientation::try_from([0, 2, 0]).is_err());
+        assert!(Orientation::try_from([0, 0, 0]).is_err());
     }
 
     #[test]
@@ -119,6 +180,21 @@ mod goatland {
         assert_eq!(mesh::genchunk(0, 0, 0).num_quads(), 375);
     }
 
+    #[test]
+    fn test_heightmap() {
+        for (chunkx, chunkz) in [(0, 0), (-3, 5), (12, -7)] {
+            assert_eq!(
+                mesh::genvoxels(chunkx, chunkz, 0),
+                mesh::genvoxels_per_voxel(chunkx, chunkz, 0)
+            );
+        }
+
+        assert_eq!(
+            mesh::genvoxels(0, 0, 0).len(),
+            mesh::heightmap(0, 0, 0).len() * 130
+        );
+    }
+
     #[test]
     fn test_mergevoxel() {
         assert_eq!(mesh::BoolVoxel(true).merge_value(), mesh::BoolVoxel(true));
@@ -128,4 +204,1174 @@ mod goatland {
     fn test_worldmap() {
         assert_eq!(noisemap::genmap().get_value(0, 0), -0.6582106524573439);
     }
+
+    #[test]
+    fn test_sample_region() {
+        let region = noisemap::Region {
+            width: 20,
+            height: 12,
+            ..Default::default()
+        };
+
+        let parallel = noisemap::sample_region(&region);
+        let serial = noisemap::sample_region_serial(&region);
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel.values.len(), 20 * 12);
+
+        // the default region covers the same points as genmap
+        let map = noisemap::genmap();
+        let full = noisemap::sample_region(&noisemap::Region::default());
+
+        for (x, z) in [(0, 0), (37, 5), (99, 99), (12, 80)] {
+            assert_eq!(full.get(x, z), map.get_value(x, z));
+        }
+    }
+
+    #[test]
+    fn test_sweep() {
+        let floor = |position: bevy_math::IVec3| position.y < 10;
+        let aabb = physics::Aabb::from_feet(Vec3::new(0.5, 10.5, 0.5), 0.3, 1.8);
+
+        let (offset, hit) = physics::sweep(&aabb, Vec3::new(0., -1., 0.), floor);
+
+        assert_eq!(offset, Vec3::new(0., -0.5, 0.));
+        assert!(hit.y);
+    }
+
+    #[test]
+    fn test_sweep_step() {
+        let ledge = |position: bevy_math::IVec3| position.y < 10 || position == (2, 10, 0).into();
+        let aabb = physics::Aabb::from_feet(Vec3::new(0.5, 10., 0.5), 0.3, 1.8);
+
+        let (blocked, hit) = physics::sweep(&aabb, Vec3::new(2., 0., 0.), ledge);
+        assert!(hit.x);
+        assert!((blocked.x - 1.2).abs() < 1e-5);
+
+        let (low, _) = physics::sweep_step(&aabb, Vec3::new(2., 0., 0.), 0.6, true, ledge);
+        assert_eq!(low, blocked);
+
+        let (stepped, _) = physics::sweep_step(&aabb, Vec3::new(2., 0., 0.), 1., true, ledge);
+        assert_eq!(stepped, Vec3::new(2., 1., 0.));
+    }
+
+    #[test]
+    fn test_raycast() {
+        let wall = |position: bevy_math::IVec3| position.x >= 5;
+
+        let hit = physics::raycast(Vec3::new(0.5, 0.5, 0.5), Vec3::X, 10., wall).unwrap();
+
+        assert_eq!(hit.block, (5, 0, 0).into());
+        assert_eq!(hit.normal, (-1, 0, 0).into());
+        assert_eq!(hit.distance, 4.5);
+
+        assert_eq!(
+            physics::raycast(Vec3::new(0.5, 0.5, 0.5), Vec3::X, 4., wall),
+            None
+        );
+    }
+
+    #[test]
+    fn test_world_time() {
+        let mut clock = time::WorldTime {
+            day: 0,
+            time_of_day: 0.75,
+        };
+
+        clock.advance(300., 1200.);
+
+        assert_eq!(clock.day, 1);
+        assert_eq!(clock.time_of_day, 0.);
+        assert_eq!(clock.daylight(), 0.);
+
+        clock.advance(600., 1200.);
+
+        assert_eq!(clock.daylight(), 1.);
+        assert!((clock.sun_direction() - Vec3::Y).length() < 1e-5);
+    }
+
+    fn layered_store(layers: &[(i32, &str)]) -> chunk::VoxelStore {
+        let mut store = chunk::VoxelStore::new(block::BlockRegistry::default());
+        let mut data = chunk::ChunkData::default();
+
+        for (y, name) in layers {
+            let id = store.registry.id(name).unwrap();
+
+            for x in 0..16 {
+                for z in 0..16 {
+                    data.set_block(bevy_math::IVec3::new(x, *y, z), id);
+                }
+            }
+        }
+
+        store.insert(bevy_math::IVec2::ZERO, data);
+        store
+    }
+
+    #[test]
+    fn test_sky_light() {
+        let floor: Vec<_> = (0..10).map(|y| (y, "stone")).collect();
+        let mut store = layered_store(&floor);
+        let stone = store.registry.id("stone").unwrap();
+
+        let below = bevy_math::IVec3::new(5, 10, 5);
+        let above = bevy_math::IVec3::new(5, 11, 5);
+
+        assert_eq!(store.light(below).sky(), 15);
+        assert_eq!(store.light(below - bevy_math::IVec3::Y).sky(), 0);
+
+        store.replace(above, stone);
+        assert_eq!(store.light(below).sky(), 14);
+
+        store.replace(above, block::BlockId::AIR);
+        assert_eq!(store.light(below).sky(), 15);
+    }
+
+    #[test]
+    fn test_block_light() {
+        let mut store = layered_store(&[(9, "stone"), (11, "stone")]);
+        let torch = store.registry.id("torch").unwrap();
+
+        let position = bevy_math::IVec3::new(8, 10, 8);
+
+        assert_eq!(store.light(position), chunk::Light::new(0, 0));
+
+        store.replace(position, torch);
+        assert_eq!(store.light(position).block(), 14);
+        assert_eq!(store.light(position + bevy_math::IVec3::X * 2).block(), 12);
+        assert_eq!(store.light(position + bevy_math::IVec3::Y * 2).block(), 0);
+
+        store.replace(position, block::BlockId::AIR);
+        assert_eq!(store.light(position + bevy_math::IVec3::X * 2).block(), 0);
+    }
+
+    #[test]
+    fn test_section_visibility() {
+        use visibility::{section_visibility, SECTION_SIZE};
+
+        let open = section_visibility(SECTION_SIZE, |_| false);
+        assert_eq!(open, visibility::SectionVisibility::all());
+
+        let solid = section_visibility(SECTION_SIZE, |_| true);
+        assert!((0..6).all(|a| (0..6).all(|b| !solid.connects(a, b))));
+
+        // a stone floor splits the section in two, faces 2 and 3 are -Y and +Y
+        let floor = section_visibility(SECTION_SIZE, |position| position.y == 8);
+        assert!(!floor.connects(2, 3));
+        assert!(floor.connects(0, 1));
+        assert!(floor.connects(2, 4));
+        assert!(floor.connects(3, 5));
+
+        // a sealed air pocket touches no face
+        let pocket = section_visibility(SECTION_SIZE, |position| {
+            !(position.cmpge(bevy_math::IVec3::splat(6)).all()
+                && position.cmple(bevy_math::IVec3::splat(9)).all())
+        });
+        assert!((0..6).all(|a| (0..6).all(|b| !pocket.connects(a, b))));
+
+        // a vertical shaft only links the top and bottom
+        let shaft = section_visibility(SECTION_SIZE, |position| position.x != 4 || position.z != 4);
+        assert!(shaft.connects(2, 3));
+        assert!(!shaft.connects(0, 1));
+        assert!(!shaft.connects(0, 3));
+    }
+
+    #[test]
+    fn test_visible_sections() {
+        use bevy_math::IVec3;
+        use visibility::{visible_sections, SectionVisibility};
+
+        // open sky above section 3, solid ground below
+        let graph = |section: IVec3| {
+            if section.x.abs() > 2 || section.z.abs() > 2 {
+                return None;
+            }
+
+            Some(match section.y {
+                y if y >= 3 => SectionVisibility::all(),
+                _ => SectionVisibility::default(),
+            })
+        };
+
+        let camera = coords::BlockPos::new(8, 60, 8);
+        let visible = visible_sections(camera, 8, graph, |_| true);
+
+        assert!(visible.contains(&IVec3::new(0, 3, 0)));
+        assert!(visible.contains(&IVec3::new(2, 5, -2)));
+        assert!(visible.contains(&IVec3::new(1, 2, 1)));
+        assert!(!visible.contains(&IVec3::new(0, 1, 0)));
+        assert!(!visible.contains(&IVec3::new(3, 3, 0)));
+
+        // from inside the ground only the direct neighbours show
+        let buried = visible_sections(coords::BlockPos::new(8, 10, 8), 8, graph, |_| true);
+        assert!(buried.contains(&IVec3::new(0, 1, 0)));
+        assert!(!buried.contains(&IVec3::new(2, 0, 0)));
+
+        let behind = visible_sections(camera, 8, graph, |section| section.x >= 0);
+        assert!(!behind.contains(&IVec3::new(-1, 3, 0)));
+
+        let near = visible_sections(camera, 1, graph, |_| true);
+        assert!(!near.contains(&IVec3::new(2, 3, 0)));
+
+        // outside the loaded chunks nothing occludes, only the view culls
+        let outside = coords::BlockPos::new(16 * 4 + 8, 60, 8);
+        let lost = visible_sections(outside, 8, graph, |section| section.x >= 0);
+        assert!(lost.contains(&IVec3::new(0, 1, 0)));
+        assert!(lost.contains(&IVec3::new(2, 5, -2)));
+        assert!(!lost.contains(&IVec3::new(-1, 3, 0)));
+        assert!(!lost.contains(&IVec3::new(3, 3, 0)));
+    }
+
+    #[test]
+    fn test_frustum() {
+        use bevy_math::Mat4;
+
+        let projection = Mat4::perspective_infinite_reverse_rh(1.2, 1., 0.1);
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let frustum = visibility::Frustum::from_matrix(projection * view);
+
+        assert!(frustum.intersects_aabb(Vec3::new(-1., -1., -11.), Vec3::new(1., 1., -9.)));
+        assert!(!frustum.intersects_aabb(Vec3::new(-1., -1., 9.), Vec3::new(1., 1., 11.)));
+        assert!(!frustum.intersects_aabb(Vec3::new(50., -1., -11.), Vec3::new(52., 1., -9.)));
+        assert!(frustum.intersects_aabb(Vec3::splat(-1000.), Vec3::splat(1000.)));
+    }
+
+    #[test]
+    fn test_lod() {
+        use block_mesh::ndshape::ConstShape;
+        use mesh::ChunkShape;
+
+        assert_eq!(lod::lod_scale(0), 1);
+        assert_eq!(lod::lod_scale(5), 2);
+        assert_eq!(lod::lod_scale(9), 4);
+        assert_eq!(lod::lod_scale(30), 8);
+
+        let registry = block::BlockRegistry::default();
+        let voxels = mesh::genvoxels(0, 0, 0);
+
+        for scale in [1, 2, 4, 8] {
+            let lod = lod::genlod(0, 0, 0, scale, &registry);
+            let tops: Vec<_> = lod
+                .positions
+                .iter()
+                .zip(lod.normals.iter())
+                .filter(|(_, normal)| normal[1] > 0.5)
+                .map(|(position, _)| *position)
+                .collect();
+
+            assert_eq!(tops.len() as i32, (16 / scale).pow(2) * 4);
+
+            // skirts reach the world bottom on every border
+            assert!(lod.positions.iter().any(|p| p[0] == 1. && p[1] == 0.));
+            assert!(lod.positions.iter().any(|p| p[0] == 17. && p[1] == 0.));
+            assert!(lod.positions.iter().any(|p| p[2] == 1. && p[1] == 0.));
+            assert!(lod.positions.iter().any(|p| p[2] == 17. && p[1] == 0.));
+
+            if scale == 1 {
+                // full scale tops sit on the generated surface
+                for [x, y, z] in tops.iter().step_by(4) {
+                    let [x, y, z] = [*x as u32, *y as u32, *z as u32];
+
+                    let below = ChunkShape::linearize([x, y - 1, z]) as usize;
+                    let above = ChunkShape::linearize([x, y, z]) as usize;
+
+                    assert_eq!(voxels[below], mesh::BoolVoxel(true));
+                    assert_eq!(voxels[above], mesh::BoolVoxel(false));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_coords() {
+        let block = coords::BlockPos::new(-1, 5, -17);
+
+        assert_eq!(block.chunk(), coords::ChunkPos::new(-1, -2));
+        assert_eq!(
+            block.local().unwrap().get(),
+            bevy_math::IVec3::new(15, 5, 15)
+        );
+        assert_eq!(
+            coords::BlockPos::from_world(Vec3::new(-0.2, 5.5, -16.01)),
+            block
+        );
+
+        assert!(coords::BlockPos::new(0, -1, 0).local().is_none());
+        assert!(coords::BlockPos::new(0, 130, 0).local().is_none());
+    }
+
+    #[test]
+    fn test_surface() {
+        let store = layered_store(&[(0, "stone"), (4, "stone"), (5, "torch")]);
+
+        assert_eq!(store.surface(bevy_math::IVec2::new(3, 7)), Some(4));
+        assert_eq!(store.surface(bevy_math::IVec2::new(16, 0)), None);
+    }
+
+    #[test]
+    fn test_store_remove() {
+        let mut store = layered_store(&[(0, "stone")]);
+        assert_eq!(store.coords().collect::<Vec<_>>(), [bevy_math::IVec2::ZERO]);
+
+        assert!(store.remove(bevy_math::IVec2::ZERO).is_some());
+        assert!(store.remove(bevy_math::IVec2::ZERO).is_none());
+        assert!(store.is_empty());
+        assert!(!store.is_solid(bevy_math::IVec3::ZERO));
+
+        store.insert(bevy_math::IVec2::ONE, chunk::ChunkData::default());
+        store.clear();
+        assert!(store.is_empty());
+        assert!(store.registry.id("stone").is_some());
+    }
+
+    #[test]
+    fn test_store_edited() {
+        let mut store = layered_store(&[(0, "stone")]);
+        assert!(!store.is_edited(bevy_math::IVec2::ZERO));
+
+        store.replace(bevy_math::IVec3::new(3, 0, 7), block::BlockId::AIR);
+        assert!(store.is_edited(bevy_math::IVec2::ZERO));
+        assert!(!store.is_edited(bevy_math::IVec2::ONE));
+
+        store.remove(bevy_math::IVec2::ZERO);
+        assert!(!store.is_edited(bevy_math::IVec2::ZERO));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_block_parts(
+            x in -1_000_000..1_000_000,
+            y in 0..chunk::CHUNK_HEIGHT,
+            z in -1_000_000..1_000_000
+        ) {
+            let block = coords::BlockPos::new(x, y, z);
+            let chunk = block.chunk();
+            let local = block.local().unwrap().get();
+
+            proptest::prop_assert_eq!(chunk.0.x, (x as f64 / 16.).floor() as i32);
+            proptest::prop_assert_eq!(chunk.0.y, (z as f64 / 16.).floor() as i32);
+            proptest::prop_assert!((0..16).contains(&local.x) && (0..16).contains(&local.z));
+            proptest::prop_assert_eq!(local.y, y);
+
+            let local = coords::LocalPos::new(local).unwrap();
+            proptest::prop_assert_eq!(coords::BlockPos::from_parts(chunk, local), block);
+        }
+
+        #[test]
+        fn test_block_world(
+            x in -100_000..100_000,
+            y in -200..200,
+            z in -100_000..100_000,
+            offset in proptest::array::uniform3(0_f32..0.9)
+        ) {
+            let block = coords::BlockPos::new(x, y, z);
+            let point = block.as_vec3() + Vec3::from(offset);
+
+            proptest::prop_assert_eq!(coords::BlockPos::from_world(point), block);
+            proptest::prop_assert_eq!(coords::ChunkPos::from_world(point), block.chunk());
+            proptest::prop_assert_eq!(coords::BlockPos::from_world(block.center()), block);
+        }
+
+        #[test]
+        fn test_block_neighbours(
+            x in -1_000_000..1_000_000,
+            y in -200..200,
+            z in -1_000_000..1_000_000
+        ) {
+            let block = coords::BlockPos::new(x, y, z);
+            let neighbours: Vec<_> = block.neighbours().collect();
+
+            proptest::prop_assert_eq!(neighbours.len(), 6);
+
+            for neighbour in neighbours {
+                let step = neighbour.0 - block.0;
+
+                proptest::prop_assert_eq!(step.abs().to_array().iter().sum::<i32>(), 1);
+                proptest::prop_assert!(neighbour.chunk().distance(block.chunk()) <= 1);
+            }
+
+            let chunk = block.chunk();
+            proptest::prop_assert_eq!(chunk.neighbours().count(), 4);
+            proptest::prop_assert!(chunk.neighbours().all(|side| side.distance(chunk) == 1));
+            proptest::prop_assert_eq!(chunk.around(2).count(), 25);
+        }
+    }
+
+    #[test]
+    fn test_world_pos() {
+        use origin::{WorldOrigin, WorldPos};
+
+        let far = WorldPos::new(
+            [40_000_000_000, 64, -40_000_000_000],
+            Vec3::new(0.25, 0.5, 0.75),
+        );
+
+        let moved = far.translate(Vec3::new(1., -0.75, -1.5));
+        assert_eq!(moved.block, [40_000_000_001, 63, -40_000_000_001]);
+        assert_eq!(moved.offset, Vec3::new(0.25, 0.75, 0.25));
+
+        // the voxel store only addresses blocks in the i32 range
+        assert_eq!(moved.block_pos(), None);
+        assert_eq!(
+            WorldPos::new([-3, 64, 7], Vec3::new(0.5, 0., 0.5)).block_pos(),
+            Some(coords::BlockPos::new(-3, 64, 7))
+        );
+
+        // transforms near the origin keep the precision of the offset
+        let origin = WorldOrigin([40_000_000_000, 0, -40_000_000_000]);
+        assert_eq!(origin.local(&moved), Vec3::new(1.25, 63.75, -0.75));
+        assert_eq!(origin.position(origin.local(&moved)), moved);
+
+        let origin = WorldOrigin([-1_000_000, 0, 2_000_000]);
+        let block = bevy_math::IVec3::new(3, 10, -2);
+
+        assert_eq!(
+            origin.to_world(block),
+            bevy_math::IVec3::new(-999_997, 10, 1_999_998)
+        );
+        assert_eq!(origin.to_local(origin.to_world(block)), block);
+    }
+
+    #[test]
+    fn test_rebase() {
+        use origin::WorldOrigin;
+
+        let origin = WorldOrigin([160, 0, -32]);
+
+        assert_eq!(origin.rebased(Vec3::new(100., 80., -100.), 512.), None);
+
+        let rebased = origin.rebased(Vec3::new(600., 80., -20.), 512.).unwrap();
+        assert_eq!(rebased, WorldOrigin([160 + 592, 0, -32 - 32]));
+
+        // entities keep their world position through the shift
+        let camera = Vec3::new(600., 80., -20.);
+        let moved = camera + origin.shift(&rebased);
+
+        assert_eq!(rebased.position(moved), origin.position(camera));
+        assert!(moved.x.abs() < 16. && moved.z.abs() < 16.);
+    }
+
+    #[test]
+    fn test_block_state() {
+        use orientation::Orientation;
+        use state::{Axis, BlockState, Half};
+
+        let state = BlockState::default()
+            .with_facing(Orientation::Left)
+            .with_axis(Axis::Z)
+            .with_half(Half::Top);
+
+        assert_eq!(state.facing(), Orientation::Left);
+        assert_eq!(state.axis(), Axis::Z);
+        assert_eq!(state.half(), Half::Top);
+        assert_eq!(state.with_half(Half::Bottom).facing(), Orientation::Left);
+
+        for bits in 0..64 {
+            let state = BlockState(bits);
+
+            for face in Orientation::iter() {
+                assert_eq!(state.invert(state.apply(face.normal())), face.normal());
+            }
+        }
+
+        // a furnace turned to +X shows its front on the +X face
+        let furnace = BlockState::default().with_facing(Orientation::Right);
+        assert_eq!(
+            furnace.model_face(Orientation::Right),
+            (Orientation::Front, 0)
+        );
+        assert_eq!(furnace.model_face(Orientation::Front).0, Orientation::Left);
+        assert_eq!(furnace.model_face(Orientation::Top), (Orientation::Top, 1));
+
+        // a log along X shows its rings on the X faces
+        let log = BlockState::default().with_axis(Axis::X);
+        assert_eq!(log.model_face(Orientation::Right).0, Orientation::Top);
+        assert_eq!(log.model_face(Orientation::Left).0, Orientation::Bottom);
+        assert_ne!(log.model_face(Orientation::Front).1, 0);
+    }
+
+    #[test]
+    fn test_placement() {
+        use bevy_math::IVec3;
+        use orientation::Orientation;
+        use state::{placement, Axis, Half, StateProperties};
+
+        let registry = block::BlockRegistry::default();
+        let furnace = registry.get(registry.id("furnace").unwrap()).states;
+        let log = registry.get(registry.id("log").unwrap()).states;
+        let stairs = registry.get(registry.id("stairs").unwrap()).states;
+
+        // looking toward +X the front faces back at the player
+        let look = Vec3::new(0.9, -0.3, 0.2);
+        assert_eq!(Orientation::facing(look), Orientation::Right);
+        assert_eq!(
+            Orientation::facing(Vec3::new(0.1, 0.9, -0.3)),
+            Orientation::Back
+        );
+        assert_eq!(
+            placement(furnace, look, IVec3::Y, 1.).facing(),
+            Orientation::Left
+        );
+
+        assert_eq!(placement(log, look, IVec3::NEG_X, 3.5).axis(), Axis::X);
+        assert_eq!(placement(log, look, IVec3::Y, 4.).axis(), Axis::Y);
+        assert_eq!(placement(furnace, look, IVec3::X, 3.).axis(), Axis::Y);
+
+        assert_eq!(placement(stairs, look, IVec3::NEG_Y, 2.).half(), Half::Top);
+        assert_eq!(placement(stairs, look, IVec3::X, 2.7).half(), Half::Top);
+        assert_eq!(placement(stairs, look, IVec3::X, -2.7).half(), Half::Bottom);
+        assert_eq!(
+            placement(StateProperties::default(), look, IVec3::X, 2.7).0,
+            0
+        );
+    }
+
+    #[test]
+    fn test_block_shapes() {
+        use model::{Cuboid, Shape};
+        use orientation::Orientation;
+        use state::{BlockState, Half};
+
+        let cube = Cuboid::new([0.; 3], [1.; 3]);
+        let slab = Cuboid::new([0., 0., 0.], [1., 0.5, 1.]);
+
+        // a slab hides the lower half of a side but never a full face
+        assert!(cube.covers(&slab, Orientation::Left));
+        assert!(!slab.covers(&cube, Orientation::Left));
+        assert!(slab.covers(&slab, Orientation::Right));
+        assert!(slab.covers(&cube, Orientation::Bottom));
+        assert!(!slab.touches(Orientation::Top));
+
+        let top = BlockState::default().with_half(Half::Top);
+        assert_eq!(slab.rotated(top), Cuboid::new([0., 0.5, 0.], [1., 1., 1.]));
+        assert!(!slab.rotated(top).covers(&slab, Orientation::Front));
+
+        // the high step of stairs facing right sits on the left side
+        let right = BlockState::default().with_facing(Orientation::Right);
+        assert_eq!(
+            Shape::stairs().boxes(right, [false; 4])[1],
+            Cuboid::new([0., 0.5, 0.], [0.5, 1., 1.])
+        );
+
+        assert_eq!(
+            Shape::Fence.boxes(BlockState::default(), [false; 4]).len(),
+            1
+        );
+        let fence = Shape::Fence.boxes(BlockState::default(), [true, false, false, true]);
+        assert_eq!(fence.len(), 5);
+        assert!(fence.iter().any(|b| b.touches(Orientation::Left)));
+        assert!(!fence.iter().any(|b| b.touches(Orientation::Right)));
+        assert!(Shape::Cross
+            .boxes(BlockState::default(), [true; 4])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_definitions() {
+        use definition::{BlockDefinition, DefinitionError, ModelDefinition};
+        use model::Shape;
+        use orientation::Orientation;
+        use std::collections::HashMap;
+
+        let models: HashMap<String, ModelDefinition> = [
+            ("cube_top", include_str!("../../assets/models/cube_top.ron")),
+            ("slab", include_str!("../../assets/models/slab.ron")),
+            ("cross", include_str!("../../assets/models/cross.ron")),
+        ]
+        .into_iter()
+        .map(|(name, text)| (name.to_string(), ron::from_str(text).unwrap()))
+        .collect();
+
+        // a full cube model still goes through the cube mesher, tint included
+        let grass: BlockDefinition =
+            ron::from_str(include_str!("../../assets/blocks/grass.ron")).unwrap();
+        let block = grass.block("grass", &models).unwrap();
+        assert_eq!(block.shape, Shape::Cube);
+        assert_eq!(block.face_color(Orientation::Top), [0.3, 0.7, 0.2]);
+        assert_eq!(block.face_color(Orientation::Bottom), [0.45, 0.3, 0.15]);
+
+        let slab: BlockDefinition =
+            ron::from_str(r#"(model: "slab", opaque: false, textures: {"all": (0.5, 0.5, 0.5)})"#)
+                .unwrap();
+        let block = slab.block("slab", &models).unwrap();
+        assert!(!block.opaque);
+
+        match block.shape {
+            Shape::Boxes(elements) => {
+                let faces = elements[0].faces;
+                assert_eq!(faces[Orientation::Top.index()].unwrap().cull, None);
+                assert_eq!(
+                    faces[Orientation::Bottom.index()].unwrap().cull,
+                    Some(Orientation::Bottom)
+                );
+                assert_eq!(
+                    faces[Orientation::Left.index()].unwrap().color,
+                    Some([0.5; 3])
+                );
+            }
+            shape => panic!("slab built as {:?}", shape),
+        }
+
+        let plant = BlockDefinition {
+            model: "cross".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            plant.block("plant", &models).unwrap_err(),
+            DefinitionError::UnknownTexture("plant".to_string())
+        );
+
+        let stairs = BlockDefinition {
+            model: "stairs".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            stairs.block("stairs", &models).unwrap_err(),
+            DefinitionError::UnknownModel("stairs".to_string())
+        );
+    }
+
+    fn inventory_registry() -> (block::BlockRegistry, block::BlockId, block::BlockId) {
+        let mut registry = block::BlockRegistry::empty();
+        let stone = registry.register(block::Block::new("stone", [0.5; 3]));
+        let torch = registry.register(block::Block {
+            max_stack: 16,
+            ..block::Block::new("torch", [1.; 3])
+        });
+
+        (registry, stone, torch)
+    }
+
+    #[test]
+    fn test_inventory_stacking() {
+        use inventory::{Inventory, ItemStack};
+
+        let (registry, stone, torch) = inventory_registry();
+        let mut inventory = Inventory::new(4);
+
+        assert_eq!(inventory.insert(ItemStack::new(stone, 40), &registry), 0);
+        assert_eq!(inventory.insert(ItemStack::new(torch, 20), &registry), 0);
+        assert_eq!(inventory.get(1), Some(ItemStack::new(torch, 16)));
+        assert_eq!(inventory.get(2), Some(ItemStack::new(torch, 4)));
+
+        // existing stacks fill up before a new slot is taken
+        assert_eq!(inventory.insert(ItemStack::new(stone, 30), &registry), 0);
+        assert_eq!(inventory.get(0), Some(ItemStack::new(stone, 64)));
+        assert_eq!(inventory.get(3), Some(ItemStack::new(stone, 6)));
+
+        assert_eq!(inventory.insert(ItemStack::new(torch, 20), &registry), 8);
+        assert_eq!(inventory.get(2), Some(ItemStack::new(torch, 16)));
+        assert_eq!(inventory.insert(ItemStack::new(stone, 100), &registry), 42);
+        assert!(!inventory.has_room(stone, &registry));
+        assert!(!inventory.has_room(torch, &registry));
+
+        inventory.remove(1, 1);
+        assert!(inventory.has_room(torch, &registry));
+        assert!(!inventory.has_room(stone, &registry));
+
+        // an empty slot takes anything
+        inventory.remove(1, 15);
+        assert!(inventory.has_room(stone, &registry));
+    }
+
+    #[test]
+    fn test_inventory_splitting() {
+        use inventory::{Inventory, ItemStack};
+
+        let (registry, stone, _) = inventory_registry();
+        let mut inventory = Inventory::new(4);
+        inventory.insert(ItemStack::new(stone, 7), &registry);
+
+        assert_eq!(inventory.split(0), Some(ItemStack::new(stone, 4)));
+        assert_eq!(inventory.get(0), Some(ItemStack::new(stone, 3)));
+        assert_eq!(inventory.remove(0, 10), Some(ItemStack::new(stone, 3)));
+        assert_eq!(inventory.get(0), None);
+        assert!(inventory.is_empty());
+
+        inventory.insert(ItemStack::new(stone, 1), &registry);
+        assert_eq!(inventory.split(0), Some(ItemStack::new(stone, 1)));
+        assert_eq!(inventory.split(0), None);
+        assert_eq!(inventory.remove(9, 1), None);
+    }
+
+    #[test]
+    fn test_inventory_merging() {
+        use inventory::{Inventory, ItemStack};
+
+        let (registry, stone, torch) = inventory_registry();
+        let mut inventory = Inventory::new(4);
+        inventory.insert(ItemStack::new(torch, 28), &registry);
+        inventory.insert(ItemStack::new(stone, 5), &registry);
+
+        // slot 0 is full so nothing moves, slot 1 only takes what fits
+        inventory.merge(1, 0, &registry);
+        assert_eq!(inventory.get(0), Some(ItemStack::new(torch, 16)));
+        assert_eq!(inventory.get(1), Some(ItemStack::new(torch, 12)));
+
+        inventory.remove(0, 10);
+        inventory.merge(1, 0, &registry);
+        assert_eq!(inventory.get(0), Some(ItemStack::new(torch, 16)));
+        assert_eq!(inventory.get(1), Some(ItemStack::new(torch, 2)));
+
+        // different blocks swap places
+        inventory.merge(2, 1, &registry);
+        assert_eq!(inventory.get(1), Some(ItemStack::new(stone, 5)));
+        assert_eq!(inventory.get(2), Some(ItemStack::new(torch, 2)));
+
+        inventory.merge(2, 3, &registry);
+        assert_eq!(inventory.get(2), None);
+        assert_eq!(inventory.get(3), Some(ItemStack::new(torch, 2)));
+    }
+
+    #[test]
+    fn test_hotbar() {
+        use inventory::{Inventory, ItemStack, HOTBAR_SIZE};
+
+        let (registry, stone, _) = inventory_registry();
+        let mut inventory = Inventory::default();
+        inventory.insert(ItemStack::new(stone, 3), &registry);
+
+        assert_eq!(inventory.held(), Some(ItemStack::new(stone, 3)));
+
+        inventory.scroll(-1);
+        assert_eq!(inventory.selected(), HOTBAR_SIZE - 1);
+        assert_eq!(inventory.held(), None);
+        inventory.scroll(2);
+        assert_eq!(inventory.selected(), 1);
+
+        // storage slots are not in the hotbar
+        inventory.select(HOTBAR_SIZE);
+        assert_eq!(inventory.selected(), 1);
+
+        let saved = ron::to_string(&inventory).unwrap();
+        assert_eq!(ron::from_str::<Inventory>(&saved).unwrap(), inventory);
+    }
+
+    fn recipe(text: &str, registry: &block::BlockRegistry) -> crafting::Recipe {
+        ron::from_str::<crafting::RecipeDefinition>(text)
+            .unwrap()
+            .recipe(registry)
+            .unwrap()
+    }
+
+    fn grid(registry: &block::BlockRegistry, rows: &[&str]) -> Vec<Option<block::BlockId>> {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .map(|cell| match cell {
+                'p' => registry.id("planks"),
+                'l' => registry.id("log"),
+                's' => registry.id("stone"),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shaped_recipes() {
+        let registry = block::BlockRegistry::default();
+        let stairs = recipe(include_str!("../../assets/recipes/stairs.ron"), &registry);
+
+        assert_eq!(stairs.result.block, registry.id("stairs").unwrap());
+        assert_eq!(stairs.result.count, 4);
+        assert!(stairs.matches(&grid(&registry, &["p  ", "pp ", "ppp"]), 3));
+        assert!(!stairs.matches(&grid(&registry, &["p  ", "pp ", "pps"]), 3));
+        assert!(!stairs.matches(&grid(&registry, &["p  ", "pp ", "pp "]), 3));
+        assert!(!stairs.matches(&grid(&registry, &["ppp", "pp ", "p  "]), 3));
+
+        // smaller patterns match anywhere on the grid
+        let slab = recipe(include_str!("../../assets/recipes/slab.ron"), &registry);
+        assert!(slab.matches(&grid(&registry, &["   ", "   ", "sss"]), 3));
+        assert!(slab.matches(&grid(&registry, &["sss", "   ", "   "]), 3));
+        assert!(!slab.matches(&grid(&registry, &["ss ", "s  ", "   "]), 3));
+        assert!(!slab.matches(&grid(&registry, &["ss", "s "]), 2));
+
+        let fence = recipe(include_str!("../../assets/recipes/fence.ron"), &registry);
+        assert!(fence.matches(&grid(&registry, &["   ", "plp", "plp"]), 3));
+        assert!(!fence.matches(&grid(&registry, &["   ", "plp", "lpp"]), 3));
+        assert!(!fence.matches(&grid(&registry, &["   ", "   ", "   "]), 3));
+    }
+
+    #[test]
+    fn test_mirrored_recipes() {
+        let registry = block::BlockRegistry::default();
+        let stairs = recipe(include_str!("../../assets/recipes/stairs.ron"), &registry);
+
+        assert!(stairs.matches(&grid(&registry, &["  p", " pp", "ppp"]), 3));
+        assert!(!stairs.matches(&grid(&registry, &["ppp", " pp", "  p"]), 3));
+
+        let hook = recipe(
+            r#"Shaped(pattern: ["pl", "p "], key: {'p': "planks", 'l': "log"}, result: "torch")"#,
+            &registry,
+        );
+        assert!(hook.matches(&grid(&registry, &["pl ", "p  ", "   "]), 3));
+        assert!(hook.matches(&grid(&registry, &[" lp", "  p", "   "]), 3));
+        assert!(!hook.matches(&grid(&registry, &[" pl", "  p", "   "]), 3));
+        assert!(!hook.matches(&grid(&registry, &["   ", "p  ", "pl "]), 3));
+    }
+
+    #[test]
+    fn test_shapeless_recipes() {
+        let registry = block::BlockRegistry::default();
+        let planks = recipe(include_str!("../../assets/recipes/planks.ron"), &registry);
+
+        assert!(planks.matches(&grid(&registry, &["   ", " l ", "   "]), 3));
+        assert!(planks.matches(&grid(&registry, &["l"]), 1));
+        assert!(!planks.matches(&grid(&registry, &["l  ", " l ", "   "]), 3));
+        assert!(!planks.matches(&grid(&registry, &["   ", "   ", "   "]), 3));
+
+        let mixed = recipe(
+            r#"Shapeless(ingredients: ["stone", "log", "stone"], result: "furnace")"#,
+            &registry,
+        );
+        assert!(mixed.matches(&grid(&registry, &["s  ", "  l", " s "]), 3));
+        assert!(mixed.matches(&grid(&registry, &["lss"]), 3));
+        assert!(!mixed.matches(&grid(&registry, &["ls ", "   ", "   "]), 3));
+        assert!(!mixed.matches(&grid(&registry, &["lsp", "s  ", "   "]), 3));
+    }
+
+    #[test]
+    fn test_crafting() {
+        use crafting::RecipeBook;
+        use inventory::ItemStack;
+
+        let registry = block::BlockRegistry::default();
+        let log = registry.id("log").unwrap();
+        let planks = registry.id("planks").unwrap();
+
+        let mut book = RecipeBook::default();
+        book.add(recipe(
+            include_str!("../../assets/recipes/planks.ron"),
+            &registry,
+        ));
+        book.add(recipe(
+            include_str!("../../assets/recipes/fence.ron"),
+            &registry,
+        ));
+
+        let mut slots = [None; 9];
+        slots[4] = Some(ItemStack::new(log, 2));
+
+        assert_eq!(book.craft(&mut slots, 3), Some(ItemStack::new(planks, 4)));
+        assert_eq!(slots[4], Some(ItemStack::new(log, 1)));
+        assert_eq!(book.craft(&mut slots, 3), Some(ItemStack::new(planks, 4)));
+        assert_eq!(slots[4], None);
+        assert_eq!(book.craft(&mut slots, 3), None);
+    }
+
+    #[test]
+    fn test_recipe_errors() {
+        use crafting::{RecipeDefinition, RecipeError};
+
+        let registry = block::BlockRegistry::default();
+        let error = |text: &str| {
+            ron::from_str::<RecipeDefinition>(text)
+                .unwrap()
+                .recipe(&registry)
+                .unwrap_err()
+        };
+
+        assert_eq!(
+            error(r##"Shaped(pattern: ["#x"], key: {'#': "stone"}, result: "slab")"##),
+            RecipeError::UnknownKey('x')
+        );
+        assert_eq!(
+            error(r###"Shaped(pattern: ["##", "#"], key: {'#': "stone"}, result: "slab")"###),
+            RecipeError::Ragged
+        );
+        assert_eq!(
+            error(r#"Shaped(pattern: ["  "], key: {}, result: "slab")"#),
+            RecipeError::Empty
+        );
+        assert_eq!(
+            error(r#"Shapeless(ingredients: ["gold"], result: "slab")"#),
+            RecipeError::UnknownBlock("gold".to_string())
+        );
+    }
+
+    #[test]
+    fn test_biomes() {
+        use biome::{planet_point, Biome, BLOCKS_PER_UNIT};
+
+        assert_eq!(Biome::from_elevation(-0.3), Biome::Ocean);
+        assert_eq!(Biome::from_elevation(0.), Biome::Plains);
+        assert_eq!(Biome::from_elevation(0.3), Biome::Hills);
+        assert_eq!(Biome::from_elevation(0.9), Biome::Mountains);
+
+        assert_eq!(Biome::from_height(4), Biome::Plains);
+        assert_eq!(Biome::from_height(10), Biome::Plains);
+        assert_eq!(Biome::from_height(13), Biome::Hills);
+        assert_eq!(Biome::from_height(15), Biome::Mountains);
+        assert_eq!(Biome::from_height(20), Biome::Mountains);
+
+        let edge = BLOCKS_PER_UNIT as i32;
+        assert_eq!(
+            planet_point(bevy_math::IVec2::new(edge, -edge / 2)),
+            [1., -0.5, 0.]
+        );
+    }
+
+    /// Floor of stone at y = 9 with extra blocks on it.
+    fn path_store(blocks: &[(bevy_math::IVec3, &str)]) -> chunk::VoxelStore {
+        let mut store = layered_store(&[(9, "stone")]);
+
+        for (position, name) in blocks {
+            let id = store.registry.id(name).unwrap();
+            store.replace(*position, id);
+        }
+
+        store
+    }
+
+    fn wall(x: i32, height: i32, gap: Option<i32>) -> Vec<(bevy_math::IVec3, &'static str)> {
+        (0..16)
+            .filter(|z| Some(*z) != gap)
+            .flat_map(|z| (10..10 + height).map(move |y| (bevy_math::IVec3::new(x, y, z), "stone")))
+            .collect()
+    }
+
+    fn walk(
+        store: &chunk::VoxelStore,
+        agent: &path::Agent,
+        goal: [i32; 3],
+    ) -> Option<Vec<bevy_math::IVec3>> {
+        let start = bevy_math::IVec3::new(2, 10, 2);
+
+        path::find_path(store, agent, start, goal.into(), path::MAX_VISITED)
+    }
+
+    #[test]
+    fn test_path_flat() {
+        let store = path_store(&[]);
+        let agent = path::Agent::new(0.7, 0.9);
+
+        let path = walk(&store, &agent, [12, 10, 5]).unwrap();
+
+        assert_eq!(path.len(), 14);
+        assert_eq!(path.first(), Some(&bevy_math::IVec3::new(2, 10, 2)));
+        assert_eq!(path.last(), Some(&bevy_math::IVec3::new(12, 10, 5)));
+        assert!(path.windows(2).all(|step| (step[1] - step[0])
+            .abs()
+            .to_array()
+            .iter()
+            .sum::<i32>()
+            == 1));
+
+        // standing in a block or in the air
+        assert_eq!(walk(&store, &agent, [12, 9, 5]), None);
+        assert_eq!(walk(&store, &agent, [12, 11, 5]), None);
+    }
+
+    #[test]
+    fn test_path_walls() {
+        let agent = path::Agent::new(0.7, 0.9);
+
+        let store = path_store(&wall(6, 2, Some(12)));
+        let path = walk(&store, &agent, [12, 10, 2]).unwrap();
+        assert!(path.contains(&bevy_math::IVec3::new(6, 10, 12)));
+
+        // no way around, chunks past the edge are not loaded
+        let store = path_store(&wall(6, 2, None));
+        assert_eq!(walk(&store, &agent, [12, 10, 2]), None);
+    }
+
+    #[test]
+    fn test_path_steps() {
+        let agent = path::Agent::new(0.7, 0.9);
+
+        // a one block step all along x = 6 and a plateau behind it
+        let plateau =
+            |height: i32| -> Vec<_> { (6..16).flat_map(|x| wall(x, height, None)).collect() };
+
+        let store = path_store(&plateau(1));
+        let path = walk(&store, &agent, [12, 11, 2]).unwrap();
+        assert!(path.contains(&bevy_math::IVec3::new(6, 11, 2)));
+
+        let store = path_store(&plateau(2));
+        assert_eq!(walk(&store, &agent, [12, 12, 2]), None);
+
+        let climber = path::Agent {
+            step_height: 2,
+            ..agent
+        };
+        assert!(walk(&store, &climber, [12, 12, 2]).is_some());
+
+        // dropping off the plateau
+        let start = bevy_math::IVec3::new(12, 12, 2);
+        let goal = bevy_math::IVec3::new(2, 10, 2);
+        assert!(path::find_path(&store, &agent, start, goal, path::MAX_VISITED).is_some());
+
+        let store = path_store(&plateau(4));
+        let start = bevy_math::IVec3::new(12, 14, 2);
+        assert_eq!(
+            path::find_path(&store, &agent, start, goal, path::MAX_VISITED),
+            None
+        );
+    }
+
+    #[test]
+    fn test_path_size_and_water() {
+        // a one block high tunnel through a wall
+        let mut blocks = wall(6, 3, None);
+        blocks.retain(|(position, _)| *position != bevy_math::IVec3::new(6, 10, 8));
+        let store = path_store(&blocks);
+
+        let goat = path::Agent::new(0.7, 0.9);
+        let tall = path::Agent::new(0.6, 1.8);
+        assert!(walk(&store, &goat, [12, 10, 2]).is_some());
+        assert_eq!(walk(&store, &tall, [12, 10, 2]), None);
+
+        // a two block wide body does not fit where a narrow one does
+        let store = path_store(&wall(6, 2, Some(12)));
+        let wide = path::Agent::new(1.4, 0.9);
+        assert!(walk(&store, &wide, [12, 10, 2]).is_none());
+
+        // a water channel across the chunk, with a bridge at z = 12
+        let water: Vec<_> = (0..16)
+            .filter(|z| *z != 12)
+            .map(|z| (bevy_math::IVec3::new(6, 10, z), "water"))
+            .collect();
+        let store = path_store(&water);
+
+        let path = walk(&store, &goat, [12, 10, 2]).unwrap();
+        assert!(path.contains(&bevy_math::IVec3::new(6, 10, 12)));
+
+        let swimmer = path::Agent {
+            avoid_liquid: false,
+            ..goat
+        };
+        assert_eq!(walk(&store, &swimmer, [12, 10, 2]).unwrap().len(), 11);
+
+        // searching a copy of the world finds the same path
+        let start = bevy_math::IVec3::new(2, 10, 2);
+        let goal = bevy_math::IVec3::new(12, 10, 2);
+        let grid = path::PathGrid::around(&store, start, goal, 16);
+        assert_eq!(
+            path::find_path(&grid, &goat, start, goal, path::MAX_VISITED),
+            Some(path)
+        );
+    }
+
+    #[test]
+    fn test_command_parsing() {
+        use command::{Command, CommandError, CommandRegistry, Coordinate, GameMode, Side};
+
+        let registry = CommandRegistry::default();
+        let parse = |line: &str| registry.parse(line).map(|(command, _)| command);
+
+        assert_eq!(
+            parse("/tp 10 ~ ~-2.5"),
+            Ok(Command::Teleport([
+                Coordinate::Absolute(10.),
+                Coordinate::Relative(0.),
+                Coordinate::Relative(-2.5),
+            ]))
+        );
+        assert_eq!(Coordinate::Relative(-2.5).resolve(12.), 9.5);
+        assert_eq!(registry.parse("seed"), Ok((Command::Seed, Side::Client)));
+        assert_eq!(parse("/time set noon"), Ok(Command::SetTime(0.5)));
+        assert_eq!(parse("/time set 0.75"), Ok(Command::SetTime(0.75)));
+        assert_eq!(
+            parse("/give  torch 12 "),
+            Ok(Command::Give {
+                block: "torch".to_string(),
+                count: 12
+            })
+        );
+        assert_eq!(
+            parse("/give stone"),
+            Ok(Command::Give {
+                block: "stone".to_string(),
+                count: 1
+            })
+        );
+        assert_eq!(
+            parse("/gamemode creative"),
+            Ok(Command::GameMode(GameMode::Creative))
+        );
+        assert_eq!(
+            registry.parse("/bind Jump"),
+            Ok((Command::Bind("Jump".to_string()), Side::Client))
+        );
+        assert_eq!(
+            registry.parse("/set view_distance 8"),
+            Ok((
+                Command::Set {
+                    name: "view_distance".to_string(),
+                    value: "8".to_string()
+                },
+                Side::Client
+            ))
+        );
+
+        assert_eq!(
+            parse("/fly"),
+            Err(CommandError::UnknownCommand("fly".to_string()))
+        );
+        assert!(matches!(parse("/tp 1 2"), Err(CommandError::Usage(_))));
+        assert!(matches!(parse("/seed 2"), Err(CommandError::Usage(_))));
+        assert!(matches!(parse("/bind"), Err(CommandError::Usage(_))));
+        assert!(matches!(parse("/set fov"), Err(CommandError::Usage(_))));
+        assert_eq!(
+            parse("/tp 1 ~x 3"),
+            Err(CommandError::InvalidArgument("~x".to_string()))
+        );
+        assert_eq!(
+            parse("/time set 1.5"),
+            Err(CommandError::InvalidArgument("1.5".to_string()))
+        );
+        assert_eq!(
+            parse("/give torch 0"),
+            Err(CommandError::InvalidArgument("0".to_string()))
+        );
+        assert_eq!(
+            parse("/gamemode hardcore"),
+            Err(CommandError::InvalidArgument("hardcore".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_command_dispatch() {
+        use command::{Command, CommandError, CommandRegistry, CommandSpec, Dispatch, Side};
+
+        let mut registry = CommandRegistry::default();
+
+        assert_eq!(
+            registry.dispatch(" hello goats ", false),
+            Ok(Dispatch::Chat("hello goats".to_string()))
+        );
+        assert_eq!(
+            registry.dispatch("/time set day", false),
+            Ok(Dispatch::Local(Command::SetTime(0.3)))
+        );
+
+        // on a server, world commands are forwarded and local ones kept
+        assert_eq!(
+            registry.dispatch("/time set day", true),
+            Ok(Dispatch::Forward("/time set day".to_string()))
+        );
+        assert_eq!(
+            registry.dispatch("/seed", true),
+            Ok(Dispatch::Local(Command::Seed))
+        );
+        assert!(matches!(
+            registry.dispatch("/time set later", true),
+            Err(CommandError::InvalidArgument(_))
+        ));
+
+        registry.register(CommandSpec {
+            name: "noon",
+            usage: "/noon",
+            side: Side::Client,
+            parse: |_| Ok(Command::SetTime(0.5)),
+        });
+
+        assert_eq!(
+            registry.dispatch("/noon", true),
+            Ok(Dispatch::Local(Command::SetTime(0.5)))
+        );
+        assert_eq!(
+            registry.iter().map(|spec| spec.name).collect::<Vec<_>>(),
+            ["bind", "gamemode", "give", "noon", "seed", "set", "time", "tp"]
+        );
+    }
 }
@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+use bevy_math::{IVec2, IVec3};
+
+use crate::chunk::{Light, CHUNK_HEIGHT, CHUNK_SIZE};
+
+pub const MAX_LIGHT: u8 = 15;
+
+const FACES: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+pub trait LightWorld {
+    /// `None` when the position is not loaded.
+    fn opaque(&self, position: IVec3) -> Option<bool>;
+    fn emission(&self, position: IVec3) -> u8;
+    fn light(&self, position: IVec3) -> Light;
+    fn set_light(&mut self, position: IVec3, light: Light);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Channel {
+    Sky,
+    Block,
+}
+
+impl Channel {
+    fn get(&self, light: Light) -> u8 {
+        match self {
+            Channel::Sky => light.sky(),
+            Channel::Block => light.block(),
+        }
+    }
+
+    fn with(&self, light: Light, level: u8) -> Light {
+        match self {
+            Channel::Sky => Light::new(level, light.block()),
+            Channel::Block => Light::new(light.sky(), level),
+        }
+    }
+
+    /// Sky light going straight down keeps its full strength.
+    fn spread(&self, level: u8, face: IVec3) -> u8 {
+        if *self == Channel::Sky && face == IVec3::NEG_Y && level == MAX_LIGHT {
+            MAX_LIGHT
+        } else {
+            level.saturating_sub(1)
+        }
+    }
+}
+
+fn transparent<W: LightWorld>(world: &W, position: IVec3) -> bool {
+    world.opaque(position) == Some(false)
+}
+
+fn set<W: LightWorld>(world: &mut W, channel: Channel, position: IVec3, level: u8) {
+    let light = world.light(position);
+    world.set_light(position, channel.with(light, level));
+}
+
+/// Flood fills light outward from every queued position.
+fn propagate<W: LightWorld>(world: &mut W, channel: Channel, queue: &mut VecDeque<IVec3>) {
+    while let Some(position) = queue.pop_front() {
+        let level = channel.get(world.light(position));
+
+        for face in FACES {
+            let neighbour = position + face;
+            let next = channel.spread(level, face);
+
+            if transparent(world, neighbour) && channel.get(world.light(neighbour)) < next {
+                set(world, channel, neighbour, next);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+/// Clears the light that came from the queued positions, the lit positions
+/// found at the border of the cleared area are queued in `relight`.
+fn unpropagate<W: LightWorld>(
+    world: &mut W,
+    channel: Channel,
+    mut queue: VecDeque<(IVec3, u8)>,
+    relight: &mut VecDeque<IVec3>,
+) {
+    while let Some((position, level)) = queue.pop_front() {
+        for face in FACES {
+            let neighbour = position + face;
+            let current = channel.get(world.light(neighbour));
+
+            if current == 0 {
+                continue;
+            }
+
+            // opaque emitters and the sky above the world are sources
+            if !transparent(world, neighbour) {
+                relight.push_back(neighbour);
+                continue;
+            }
+
+            if current < level || channel.spread(level, face) == MAX_LIGHT {
+                set(world, channel, neighbour, 0);
+                queue.push_back((neighbour, current));
+
+                let emission = world.emission(neighbour);
+
+                if channel == Channel::Block && emission > 0 {
+                    set(world, channel, neighbour, emission);
+                    relight.push_back(neighbour);
+                }
+            } else {
+                relight.push_back(neighbour);
+            }
+        }
+    }
+}
+
+/// Computes the light of a chunk that was just loaded, letting the light of
+/// the loaded neighbours flow into it.
+pub fn light_chunk<W: LightWorld>(world: &mut W, coord: IVec2) {
+    let origin = IVec3::new(coord.x * CHUNK_SIZE, 0, coord.y * CHUNK_SIZE);
+
+    let mut sky = VecDeque::new();
+    let mut block = VecDeque::new();
+
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let mut open = true;
+
+            for y in (0..CHUNK_HEIGHT).rev() {
+                let position = origin + IVec3::new(x, y, z);
+
+                open &= transparent(world, position);
+
+                let sky_level = if open { MAX_LIGHT } else { 0 };
+                let emission = world.emission(position);
+
+                world.set_light(position, Light::new(sky_level, emission));
+
+                if open {
+                    sky.push_back(position);
+                }
+
+                if emission > 0 {
+                    block.push_back(position);
+                }
+            }
+        }
+    }
+
+    for (side, step) in [
+        (IVec3::new(-1, 0, 0), IVec3::Z),
+        (IVec3::new(CHUNK_SIZE, 0, 0), IVec3::Z),
+        (IVec3::new(0, 0, -1), IVec3::X),
+        (IVec3::new(0, 0, CHUNK_SIZE), IVec3::X),
+    ] {
+        for i in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_HEIGHT {
+                let position = origin + side + step * i + IVec3::Y * y;
+                let light = world.light(position);
+
+                if light.sky() > 1 {
+                    sky.push_back(position);
+                }
+
+                if light.block() > 1 {
+                    block.push_back(position);
+                }
+            }
+        }
+    }
+
+    propagate(world, Channel::Sky, &mut sky);
+    propagate(world, Channel::Block, &mut block);
+}
+
+/// Updates the light around `position` after the block there changed.
+pub fn block_changed<W: LightWorld>(world: &mut W, position: IVec3) {
+    for channel in [Channel::Sky, Channel::Block] {
+        let mut relight = VecDeque::new();
+
+        let level = channel.get(world.light(position));
+        set(world, channel, position, 0);
+        unpropagate(
+            world,
+            channel,
+            VecDeque::from([(position, level)]),
+            &mut relight,
+        );
+
+        let emission = world.emission(position);
+
+        if channel == Channel::Block && emission > 0 {
+            set(world, channel, position, emission);
+            relight.push_back(position);
+        }
+
+        if transparent(world, position) {
+            for face in FACES {
+                let neighbour = position + face;
+
+                if channel.get(world.light(neighbour)) > 0 {
+                    relight.push_back(neighbour);
+                }
+            }
+        }
+
+        propagate(world, channel, &mut relight);
+    }
+}
@@ -0,0 +1,115 @@
+use bevy_math::Vec3;
+
+use noise::OpenSimplex;
+
+use crate::block::BlockRegistry;
+use crate::chunk::CHUNK_SIZE;
+use crate::mesh::{pushquad, terrainheight, ChunkMesh};
+
+/// Voxel downsampling of a chunk `distance` chunks away from the player,
+/// full resolution close by then 2x, 4x and 8x in rings of four chunks.
+pub fn lod_scale(distance: i32) -> i32 {
+    match distance {
+        0..=3 => 1,
+        4..=7 => 2,
+        8..=11 => 4,
+        _ => 8,
+    }
+}
+
+/// Terrain surface of a chunk meshed from its height function sampled every
+/// `scale` blocks, each sample standing for a `scale` x `scale` column.
+///
+/// Vertices are in the same padded space as full resolution meshes. Walls on
+/// the chunk border are skirts going down to the world bottom, so no crack
+/// shows against a neighbour meshed at another scale.
+pub fn genlod(
+    chunkx: i32,
+    chunkz: i32,
+    seed: u32,
+    scale: i32,
+    registry: &BlockRegistry,
+) -> ChunkMesh {
+    let noise = OpenSimplex::new(seed);
+    let cells = CHUNK_SIZE / scale;
+
+    let color = |name: &str| {
+        let [r, g, b] = registry
+            .id(name)
+            .map_or([1.; 3], |id| registry.get(id).color);
+
+        [r, g, b, 1.]
+    };
+
+    let top = color("grass");
+    let side = color("dirt");
+
+    // the full resolution generator samples the column one block further
+    let surface = |i: i32, j: i32| {
+        terrainheight(
+            &noise,
+            chunkx * CHUNK_SIZE + i * scale + 1,
+            chunkz * CHUNK_SIZE + j * scale + 1,
+        ) as f32
+            + 1.
+    };
+
+    let heights: Vec<Vec<f32>> = (0..cells)
+        .map(|i| (0..cells).map(|j| surface(i, j)).collect())
+        .collect();
+
+    let height = |i: i32, j: i32| {
+        if (0..cells).contains(&i) && (0..cells).contains(&j) {
+            Some(heights[i as usize][j as usize])
+        } else {
+            None
+        }
+    };
+
+    let size = scale as f32;
+    let mut mesh = ChunkMesh::default();
+
+    for i in 0..cells {
+        for j in 0..cells {
+            let y = heights[i as usize][j as usize];
+            let x0 = (i * scale + 1) as f32;
+            let z0 = (j * scale + 1) as f32;
+
+            pushquad(
+                &mut mesh,
+                Vec3::new(x0, y, z0),
+                Vec3::Z * size,
+                Vec3::X * size,
+                top,
+            );
+
+            let walls = [
+                (-1, 0, Vec3::new(x0, 0., z0), Vec3::Z * size, Vec3::Y),
+                (1, 0, Vec3::new(x0 + size, 0., z0), Vec3::Y, Vec3::Z * size),
+                (0, -1, Vec3::new(x0, 0., z0), Vec3::Y, Vec3::X * size),
+                (0, 1, Vec3::new(x0, 0., z0 + size), Vec3::X * size, Vec3::Y),
+            ];
+
+            for (dx, dz, corner, u, v) in walls {
+                let bottom = height(i + dx, j + dz).unwrap_or(0.);
+
+                if bottom >= y {
+                    continue;
+                }
+
+                // stretch the unit vertical edge over the wall height
+                let [u, v] = [u, v].map(|edge| {
+                    if edge == Vec3::Y {
+                        edge * (y - bottom)
+                    } else {
+                        edge
+                    }
+                });
+
+                pushquad(&mut mesh, corner + Vec3::Y * bottom, u, v, side);
+            }
+        }
+    }
+
+    mesh
+}
@@ -1,11 +1,21 @@
 use block_mesh::ndshape::{ConstShape, ConstShape3u32};
 use block_mesh::{
-    visible_block_faces, MergeVoxel, UnitQuadBuffer, Voxel, VoxelVisibility,
+    visible_block_faces, MergeVoxel, UnitQuadBuffer, UnorientedQuad, Voxel, VoxelVisibility,
     RIGHT_HANDED_Y_UP_CONFIG,
 };
 
+use bevy_math::{IVec2, IVec3, Vec3};
+
 use noise::{NoiseFn, OpenSimplex};
 
+use crate::block::{BlockId, BlockRegistry};
+use crate::chunk::{ChunkData, Light, VoxelStore, CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::coords::ChunkPos;
+use crate::light::{LightWorld, MAX_LIGHT};
+use crate::model::{Cuboid, Shape, FENCE_SIDES};
+use crate::orientation::Orientation;
+use crate::state::BlockState;
+
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub struct BoolVoxel(pub bool);
 
@@ -30,28 +40,78 @@ impl MergeVoxel for BoolVoxel {
     }
 }
 
-pub fn genchunk(chunkx: i32, chunkz: i32, seed: u32) -> UnitQuadBuffer {
+/// Side of a padded chunk, one block of each neighbour around its 16 columns.
+const PADDED: u32 = 18;
+const COLUMNS: usize = (PADDED * PADDED) as usize;
+
+pub type ChunkShape = ConstShape3u32<PADDED, 130, PADDED>;
+
+/// Lowest surface of the generated terrain, the noise only raises it.
+pub const TERRAIN_BASE: u32 = 10;
+/// Height the noise adds at most above `TERRAIN_BASE`.
+pub const TERRAIN_AMPLITUDE: u32 = 10;
+
+/// Highest full voxel of the terrain column at world `(x, z)`.
+pub fn terrainheight(noise: &OpenSimplex, x: i32, z: i32) -> u32 {
+    (noise.get([x as f64 / 10., z as f64 / 10.]) * TERRAIN_AMPLITUDE as f64) as u32 + TERRAIN_BASE
+}
+
+/// Terrain height of every padded column, indexed by `x + z * 18`.
+pub fn heightmap(chunkx: i32, chunkz: i32, seed: u32) -> [u32; COLUMNS] {
     let noise = OpenSimplex::new(seed);
 
-    type ChunkShape = ConstShape3u32<18, 130, 18>;
+    let mut heights = [0; COLUMNS];
+
+    for (i, height) in heights.iter_mut().enumerate() {
+        let x = (i as u32 % PADDED) as i32;
+        let z = (i as u32 / PADDED) as i32;
+
+        *height = terrainheight(&noise, x + chunkx * 16, z + chunkz * 16);
+    }
+
+    heights
+}
+
+pub fn genvoxels(chunkx: i32, chunkz: i32, seed: u32) -> Vec<BoolVoxel> {
+    let heights = heightmap(chunkx, chunkz, seed);
+
+    let mut voxels = vec![FULL; ChunkShape::SIZE as usize];
+
+    for i in 0..ChunkShape::SIZE {
+        let [x, y, z] = ChunkShape::delinearize(i);
+
+        voxels[i as usize] = if y <= heights[(x + z * PADDED) as usize] {
+            FULL
+        } else {
+            EMPTY
+        }
+    }
+
+    voxels
+}
 
-    let mut voxels = [FULL; ChunkShape::SIZE as usize];
+/// Evaluates the noise once per voxel instead of once per column, kept as the
+/// reference for the heightmap pass and its benchmark.
+pub fn genvoxels_per_voxel(chunkx: i32, chunkz: i32, seed: u32) -> Vec<BoolVoxel> {
+    let noise = OpenSimplex::new(seed);
+
+    let mut voxels = vec![FULL; ChunkShape::SIZE as usize];
 
     for i in 0..ChunkShape::SIZE {
         let [x, y, z] = ChunkShape::delinearize(i);
 
-        let noisey = (noise.get([
-            (x as i32 + chunkx * 16) as f64 / 10.,
-            (z as i32 + chunkz * 16) as f64 / 10.,
-        ]) * 10.) as u32
-            + 10;
+        let noisey = terrainheight(&noise, x as i32 + chunkx * 16, z as i32 + chunkz * 16);
 
         voxels[i as usize] = if y <= noisey { FULL } else { EMPTY }
     }
 
+    voxels
+}
+
+pub fn meshvoxels(voxels: &[BoolVoxel]) -> UnitQuadBuffer {
     let mut buffer = UnitQuadBuffer::new();
     visible_block_faces(
-        &voxels,
+        voxels,
         &ChunkShape {},
         [0; 3],
         [17, 129, 17],
@@ -61,3 +121,359 @@ pub fn genchunk(chunkx: i32, chunkz: i32, seed: u32) -> UnitQuadBuffer {
 
     buffer
 }
+
+pub fn genchunk(chunkx: i32, chunkz: i32, seed: u32) -> UnitQuadBuffer {
+    meshvoxels(&genvoxels(chunkx, chunkz, seed))
+}
+
+/// Padded voxel handed to the mesher, with the light of its own cell.
+#[derive(Clone, Copy)]
+pub struct ChunkVoxel {
+    pub block: BlockId,
+    pub state: BlockState,
+    pub visibility: VoxelVisibility,
+    pub light: Light,
+}
+
+impl Voxel for ChunkVoxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        self.visibility
+    }
+}
+
+#[derive(Default)]
+pub struct ChunkMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+/// Terrain blocks of a chunk: grass on the surface, a few layers of dirt, then stone.
+pub fn genblocks(chunkx: i32, chunkz: i32, seed: u32, registry: &BlockRegistry) -> ChunkData {
+    let voxels = genvoxels(chunkx, chunkz, seed);
+
+    let grass = registry.id("grass").unwrap_or(BlockId::AIR);
+    let dirt = registry.id("dirt").unwrap_or(BlockId::AIR);
+    let stone = registry.id("stone").unwrap_or(BlockId::AIR);
+    let plants = [registry.id("tall_grass"), registry.id("flower")];
+
+    let mut chunk = ChunkData::default();
+
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let mut depth = 0;
+
+            for y in (0..CHUNK_HEIGHT).rev() {
+                let index = ChunkShape::linearize([x as u32 + 1, y as u32, z as u32 + 1]);
+
+                if voxels[index as usize] == EMPTY {
+                    depth = 0;
+                    continue;
+                }
+
+                let block = match depth {
+                    0 => grass,
+                    1..=3 => dirt,
+                    _ => stone,
+                };
+
+                chunk.set_block(IVec3::new(x, y, z), block);
+
+                // a few plants on the grass, the same ones every time the chunk is generated
+                if depth == 0 && y + 1 < CHUNK_HEIGHT {
+                    let roll = scatter(chunkx * CHUNK_SIZE + x, chunkz * CHUNK_SIZE + z, seed);
+
+                    let plant = match roll % 64 {
+                        0..=3 => plants[0],
+                        4 => plants[1],
+                        _ => None,
+                    };
+
+                    if let Some(plant) = plant {
+                        chunk.set_block(IVec3::new(x, y + 1, z), plant);
+                    }
+                }
+
+                depth += 1;
+            }
+        }
+    }
+
+    chunk
+}
+
+/// Cheap integer hash of a column, stable across runs.
+fn scatter(x: i32, z: i32, seed: u32) -> u32 {
+    let mut hash = (x as u32)
+        .wrapping_mul(0x27d4_eb2d)
+        .wrapping_add((z as u32).wrapping_mul(0x1656_67b1))
+        ^ seed.wrapping_mul(0x9e37_79b9);
+
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^ hash >> 13
+}
+
+/// Copies a chunk and the borders of its neighbours into the padded layout of
+/// `ChunkShape`, unloaded neighbours are opaque so no face is built toward them.
+pub fn padchunk(store: &VoxelStore, coord: IVec2) -> Vec<ChunkVoxel> {
+    let origin = ChunkPos(coord).origin().0 - IVec3::new(1, 0, 1);
+
+    (0..ChunkShape::SIZE)
+        .map(|i| {
+            let [x, y, z] = ChunkShape::delinearize(i);
+            let position = origin + IVec3::new(x as i32, y as i32, z as i32);
+
+            match store.block(position) {
+                Some(block) => {
+                    let properties = store.registry.get(block);
+
+                    let visibility = if block == BlockId::AIR || properties.shape != Shape::Cube {
+                        VoxelVisibility::Empty
+                    } else if properties.opaque {
+                        VoxelVisibility::Opaque
+                    } else {
+                        VoxelVisibility::Translucent
+                    };
+
+                    ChunkVoxel {
+                        block,
+                        state: store.state(position),
+                        visibility,
+                        light: store.light(position),
+                    }
+                }
+                None => ChunkVoxel {
+                    block: BlockId::AIR,
+                    state: BlockState::default(),
+                    visibility: VoxelVisibility::Opaque,
+                    light: Light::default(),
+                },
+            }
+        })
+        .collect()
+}
+
+fn brightness(level: u8) -> f32 {
+    0.8_f32
+        .powi((MAX_LIGHT - level.min(MAX_LIGHT)) as i32)
+        .max(0.05)
+}
+
+/// Builds the visible faces of a padded chunk, each face colored by its block
+/// and lit by the light of the cell it faces.
+pub fn meshchunk(voxels: &[ChunkVoxel], registry: &BlockRegistry) -> ChunkMesh {
+    let mut buffer = UnitQuadBuffer::new();
+    visible_block_faces(
+        voxels,
+        &ChunkShape {},
+        [0; 3],
+        [17, 129, 17],
+        &RIGHT_HANDED_Y_UP_CONFIG.faces,
+        &mut buffer,
+    );
+
+    let mut mesh = ChunkMesh::default();
+
+    for (face, quads) in RIGHT_HANDED_Y_UP_CONFIG
+        .faces
+        .iter()
+        .zip(buffer.groups.iter())
+    {
+        let orientation = match Orientation::from_face(face) {
+            Ok(orientation) => orientation,
+            Err(_) => continue,
+        };
+
+        let normal = face.quad_mesh_normals()[0];
+        let offset = [normal[0] as i32, normal[1] as i32, normal[2] as i32];
+
+        for quad in quads.iter() {
+            let voxel = voxels[ChunkShape::linearize(quad.minimum) as usize];
+
+            let facing = ChunkShape::linearize([
+                (quad.minimum[0] as i32 + offset[0]) as u32,
+                (quad.minimum[1] as i32 + offset[1]) as u32,
+                (quad.minimum[2] as i32 + offset[2]) as u32,
+            ]);
+
+            let block = registry.get(voxel.block);
+            let level = voxels[facing as usize]
+                .light
+                .brightest()
+                .max(block.emission);
+
+            // the state picks which model face shows here and turns its texture
+            let (model, turns) = voxel.state.model_face(orientation);
+
+            let [r, g, b] = block
+                .face_color(model)
+                .map(|channel| channel * brightness(level));
+
+            let quad = UnorientedQuad::from(*quad);
+
+            let mut uvs = face.tex_coords(RIGHT_HANDED_Y_UP_CONFIG.u_flip_face, true, &quad);
+            uvs.rotate_left(turns as usize);
+
+            mesh.indices
+                .extend_from_slice(&face.quad_mesh_indices(mesh.positions.len() as u32));
+            mesh.positions
+                .extend_from_slice(&face.quad_mesh_positions(&quad, 1.));
+            mesh.normals.extend_from_slice(&face.quad_mesh_normals());
+            mesh.uvs.extend_from_slice(&uvs);
+            mesh.colors.extend_from_slice(&[[r, g, b, 1.]; 4]);
+        }
+    }
+
+    meshshapes(voxels, registry, &mut mesh);
+
+    mesh
+}
+
+/// Meshes the blocks that are not full cubes, `block_mesh` sees them as empty.
+/// Element faces are dropped when the neighbour on their cull side hides them
+/// completely, so a slab still lets the face under its top half show.
+fn meshshapes(voxels: &[ChunkVoxel], registry: &BlockRegistry, mesh: &mut ChunkMesh) {
+    let voxel = |position: IVec3| {
+        if position.y < 0 || position.y >= CHUNK_HEIGHT {
+            return None;
+        }
+
+        Some(voxels[ChunkShape::linearize(position.as_uvec3().to_array()) as usize])
+    };
+
+    for x in 1..=CHUNK_SIZE {
+        for z in 1..=CHUNK_SIZE {
+            for y in 0..CHUNK_HEIGHT {
+                let position = IVec3::new(x, y, z);
+                let center = voxels[ChunkShape::linearize(position.as_uvec3().to_array()) as usize];
+                let block = registry.get(center.block);
+
+                if center.block == BlockId::AIR || block.shape == Shape::Cube {
+                    continue;
+                }
+
+                let light = |face: Option<Orientation>| {
+                    let cell = face.and_then(|face| voxel(face.neighbour(position)));
+                    let level = cell.unwrap_or(center).light.brightest().max(block.emission);
+
+                    brightness(level)
+                };
+
+                if block.shape == Shape::Cross {
+                    let [r, g, b] = block.color.map(|channel| channel * light(None));
+                    let corner = position.as_vec3();
+
+                    for (start, across) in [
+                        (Vec3::new(0.15, 0., 0.15), Vec3::new(0.7, 0., 0.7)),
+                        (Vec3::new(0.15, 0., 0.85), Vec3::new(0.7, 0., -0.7)),
+                    ] {
+                        // both windings so the plant shows from either side
+                        pushquad(mesh, corner + start, across, Vec3::Y, [r, g, b, 1.]);
+                        pushquad(mesh, corner + start, Vec3::Y, across, [r, g, b, 1.]);
+                    }
+
+                    continue;
+                }
+
+                let connects = FENCE_SIDES.map(|side| {
+                    voxel(side.neighbour(position)).map_or(false, |neighbour| {
+                        let neighbour = registry.get(neighbour.block);
+
+                        neighbour.shape == Shape::Fence
+                            || (neighbour.shape == Shape::Cube && neighbour.opaque)
+                    })
+                });
+
+                for element in block.shape.elements(center.state, connects) {
+                    for face in Orientation::iter() {
+                        let element_face = match element.faces[face.index()] {
+                            Some(element_face) => element_face,
+                            None => continue,
+                        };
+
+                        if let Some(cull) = element_face.cull {
+                            let neighbour = voxel(cull.neighbour(position));
+
+                            if covered(&element.cuboid, cull, neighbour, registry) {
+                                continue;
+                            }
+                        }
+
+                        let (model, _) = center.state.model_face(face);
+                        let color = element_face
+                            .color
+                            .unwrap_or_else(|| block.face_color(model));
+                        let shade = light(element_face.cull);
+                        let [r, g, b] = color.map(|channel| channel * shade);
+
+                        let (corner, u, v) = boxface(&element.cuboid, face);
+                        pushquad(mesh, position.as_vec3() + corner, u, v, [r, g, b, 1.]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether the neighbour on the `face` side hides the face of `cuboid` lying
+/// on that side.
+fn covered(
+    cuboid: &Cuboid,
+    face: Orientation,
+    neighbour: Option<ChunkVoxel>,
+    registry: &BlockRegistry,
+) -> bool {
+    let neighbour = match neighbour {
+        Some(neighbour) if neighbour.block != BlockId::AIR => neighbour,
+        _ => return false,
+    };
+
+    let block = registry.get(neighbour.block);
+
+    match &block.shape {
+        Shape::Cube => block.opaque,
+        Shape::Cross => false,
+        shape => shape
+            .boxes(neighbour.state, [false; 4])
+            .iter()
+            .any(|other| other.covers(cuboid, face.opposite())),
+    }
+}
+
+/// Corner and edges of the `face` side of a box, wound to face outward.
+fn boxface(cuboid: &Cuboid, face: Orientation) -> (Vec3, Vec3, Vec3) {
+    let Cuboid { min, max } = *cuboid;
+    let size = max - min;
+
+    let [x, y, z] = [Vec3::X * size.x, Vec3::Y * size.y, Vec3::Z * size.z];
+
+    match face {
+        Orientation::Left => (min, z, y),
+        Orientation::Right => (Vec3::new(max.x, min.y, min.z), y, z),
+        Orientation::Bottom => (min, x, z),
+        Orientation::Top => (Vec3::new(min.x, max.y, min.z), z, x),
+        Orientation::Back => (min, y, x),
+        Orientation::Front => (Vec3::new(min.x, min.y, max.z), x, y),
+    }
+}
+
+/// Adds the quad `corner`, `corner + u`, `corner + u + v`, `corner + v`,
+/// facing `u x v`.
+pub(crate) fn pushquad(mesh: &mut ChunkMesh, corner: Vec3, u: Vec3, v: Vec3, color: [f32; 4]) {
+    let start = mesh.positions.len() as u32;
+    let normal = u.cross(v).normalize();
+
+    for position in [corner, corner + u, corner + u + v, corner + v] {
+        mesh.positions.push(position.to_array());
+        mesh.normals.push(normal.to_array());
+    }
+
+    mesh.uvs
+        .extend_from_slice(&[[0., 0.], [1., 0.], [1., 1.], [0., 1.]]);
+    mesh.colors.extend_from_slice(&[color; 4]);
+    mesh.indices
+        .extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+}
@@ -0,0 +1,233 @@
+use bevy_math::Vec3;
+
+use std::convert::TryFrom;
+
+use crate::orientation::Orientation;
+use crate::state::BlockState;
+
+/// Box of a block model, in block units from `0` to `1`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cuboid {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Cuboid {
+    pub fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self {
+            min: Vec3::from(min),
+            max: Vec3::from(max),
+        }
+    }
+
+    /// Same box expressed in sixteenths of a block.
+    pub fn pixels(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self::new(min.map(|v| v / 16.), max.map(|v| v / 16.))
+    }
+
+    /// Box turned by a block state around the block center.
+    pub fn rotated(&self, state: BlockState) -> Self {
+        let center = Vec3::splat(0.5);
+        let a = state_vec3(state, self.min - center) + center;
+        let b = state_vec3(state, self.max - center) + center;
+
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Whether the face of the box lies on the block side `face`.
+    pub fn touches(&self, face: Orientation) -> bool {
+        match face {
+            Orientation::Left => self.min.x <= 0.,
+            Orientation::Right => self.max.x >= 1.,
+            Orientation::Bottom => self.min.y <= 0.,
+            Orientation::Top => self.max.y >= 1.,
+            Orientation::Back => self.min.z <= 0.,
+            Orientation::Front => self.max.z >= 1.,
+        }
+    }
+
+    /// Rectangle of the box projected on the plane of `face`.
+    fn footprint(&self, face: Orientation) -> ([f32; 2], [f32; 2]) {
+        let [min, max] = [self.min, self.max].map(|corner| match face {
+            Orientation::Left | Orientation::Right => [corner.y, corner.z],
+            Orientation::Bottom | Orientation::Top => [corner.x, corner.z],
+            Orientation::Back | Orientation::Front => [corner.x, corner.y],
+        });
+
+        (min, max)
+    }
+
+    /// Whether the `face` side of this box hides the `face` side of `other`
+    /// entirely, both lying on the same block side.
+    pub fn covers(&self, other: &Cuboid, face: Orientation) -> bool {
+        if !self.touches(face) {
+            return false;
+        }
+
+        let (min, max) = self.footprint(face);
+        let (other_min, other_max) = other.footprint(face);
+
+        min[0] <= other_min[0]
+            && min[1] <= other_min[1]
+            && max[0] >= other_max[0]
+            && max[1] >= other_max[1]
+    }
+}
+
+fn state_vec3(state: BlockState, vector: Vec3) -> Vec3 {
+    let [x, y, z] = [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| state.apply(axis.as_ivec3()).as_vec3());
+
+    x * vector.x + y * vector.y + z * vector.z
+}
+
+/// Face of a model element.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ElementFace {
+    /// Color drawn instead of the block face color.
+    pub color: Option<[f32; 3]>,
+    /// Block side whose neighbour hides this face when it covers it, the face
+    /// is always drawn without one.
+    pub cull: Option<Orientation>,
+}
+
+/// Box of a model and the faces it draws.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Element {
+    pub cuboid: Cuboid,
+    /// Indexed like `Orientation::ALL`, `None` leaves the face out.
+    pub faces: [Option<ElementFace>; 6],
+}
+
+impl Element {
+    /// Every face drawn with the block colors, culled on the block sides
+    /// they lie on.
+    pub fn new(cuboid: Cuboid) -> Self {
+        Self {
+            cuboid,
+            faces: Orientation::ALL.map(|face| {
+                Some(ElementFace {
+                    color: None,
+                    cull: cuboid.touches(face).then_some(face),
+                })
+            }),
+        }
+    }
+
+    /// Element turned by a block state, its faces following the box.
+    pub fn rotated(&self, state: BlockState) -> Self {
+        let turn =
+            |face: Orientation| Orientation::try_from(state.apply(face.normal())).unwrap_or(face);
+
+        let mut faces = [None; 6];
+
+        for model in Orientation::iter() {
+            faces[turn(model).index()] = self.faces[model.index()].map(|face| ElementFace {
+                cull: face.cull.map(turn),
+                ..face
+            });
+        }
+
+        Self {
+            cuboid: self.cuboid.rotated(state),
+            faces,
+        }
+    }
+
+    /// Whether this element is the full block with every face culled on its
+    /// own side, which the cube mesher draws the same way.
+    pub fn is_cube(&self) -> bool {
+        self.cuboid == Cuboid::new([0.; 3], [1.; 3])
+            && Orientation::iter().all(|face| {
+                self.faces[face.index()].map_or(false, |element| element.cull == Some(face))
+            })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    /// Full unit cube, meshed and culled by `block_mesh`.
+    Cube,
+    /// Elements of the model as authored, turned by the block state.
+    Boxes(Vec<Element>),
+    /// Two crossed double sided quads, for plants.
+    Cross,
+    /// Post with rails toward the neighbours it connects to.
+    Fence,
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self::Cube
+    }
+}
+
+impl Shape {
+    pub fn slab() -> Self {
+        Self::Boxes(vec![Element::new(Cuboid::new([0., 0., 0.], [1., 0.5, 1.]))])
+    }
+
+    /// Steps rise toward the back, the model front is the low side.
+    pub fn stairs() -> Self {
+        Self::Boxes(vec![
+            Element::new(Cuboid::new([0., 0., 0.], [1., 0.5, 1.])),
+            Element::new(Cuboid::new([0., 0.5, 0.], [1., 1., 0.5])),
+        ])
+    }
+
+    pub fn torch() -> Self {
+        Self::Boxes(vec![Element::new(Cuboid::pixels(
+            [7., 0., 7.],
+            [9., 10., 9.],
+        ))])
+    }
+
+    /// Elements in block space, `connects` telling which of the horizontal
+    /// neighbours, indexed like `FENCE_SIDES`, a fence reaches to.
+    pub fn elements(&self, state: BlockState, connects: [bool; 4]) -> Vec<Element> {
+        match self {
+            Shape::Cube => vec![Element::new(Cuboid::new([0.; 3], [1.; 3]))],
+            Shape::Boxes(elements) => elements.iter().map(|e| e.rotated(state)).collect(),
+            Shape::Cross => Vec::new(),
+            Shape::Fence => {
+                let mut boxes = vec![Cuboid::pixels([6., 0., 6.], [10., 16., 10.])];
+
+                for (side, connected) in FENCE_SIDES.iter().zip(connects) {
+                    if !connected {
+                        continue;
+                    }
+
+                    for (bottom, top) in [(6., 9.), (12., 15.)] {
+                        let rail = match side {
+                            Orientation::Left => ([0., bottom, 7.], [6., top, 9.]),
+                            Orientation::Right => ([10., bottom, 7.], [16., top, 9.]),
+                            Orientation::Back => ([7., bottom, 0.], [9., top, 6.]),
+                            _ => ([7., bottom, 10.], [9., top, 16.]),
+                        };
+
+                        boxes.push(Cuboid::pixels(rail.0, rail.1));
+                    }
+                }
+
+                boxes.into_iter().map(Element::new).collect()
+            }
+        }
+    }
+
+    /// Boxes of `elements`, without their faces.
+    pub fn boxes(&self, state: BlockState, connects: [bool; 4]) -> Vec<Cuboid> {
+        self.elements(state, connects)
+            .into_iter()
+            .map(|element| element.cuboid)
+            .collect()
+    }
+}
+
+pub const FENCE_SIDES: [Orientation; 4] = [
+    Orientation::Left,
+    Orientation::Right,
+    Orientation::Back,
+    Orientation::Front,
+];
@@ -1,7 +1,124 @@
 use noise::{core::worley::ReturnType, utils::*, *};
 
-#[allow(non_snake_case)]
+use rayon::prelude::*;
+
+/// Rectangular grid of points sampled over the planet, `width` points from
+/// `x_bounds.0` and `height` rows from `z_bounds.0`, like `PlaneMapBuilder`.
+#[derive(Clone, Copy, Debug)]
+pub struct Region {
+    pub width: usize,
+    pub height: usize,
+    pub x_bounds: (f64, f64),
+    pub z_bounds: (f64, f64),
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Self {
+            width: 100,
+            height: 100,
+            x_bounds: (-1., 1.),
+            z_bounds: (-1., 1.),
+        }
+    }
+}
+
+impl Region {
+    fn point(&self, x: usize, z: usize) -> [f64; 3] {
+        let step_x = (self.x_bounds.1 - self.x_bounds.0) / self.width as f64;
+        let step_z = (self.z_bounds.1 - self.z_bounds.0) / self.height as f64;
+
+        [
+            self.x_bounds.0 + step_x * x as f64,
+            self.z_bounds.0 + step_z * z as f64,
+            0.,
+        ]
+    }
+}
+
+/// Planet elevation of a region, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeightMap {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f64>,
+}
+
+impl HeightMap {
+    pub fn get(&self, x: usize, z: usize) -> f64 {
+        self.values[z * self.width + x]
+    }
+}
+
+/// Rows sampled by each rayon task, the graph is built once per task since its
+/// `Cache` nodes cannot be shared between threads.
+const ROWS_PER_TASK: usize = 8;
+
+/// Samples a region of the planet in parallel across rows.
+pub fn sample_region(region: &Region) -> HeightMap {
+    let mut values = vec![0.; region.width * region.height];
+
+    if region.width > 0 {
+        values
+            .par_chunks_mut(region.width * ROWS_PER_TASK)
+            .enumerate()
+            .for_each(|(task, rows)| {
+                with_planet(|planet| sample_rows(planet, region, task * ROWS_PER_TASK, rows))
+            });
+    }
+
+    HeightMap {
+        width: region.width,
+        height: region.height,
+        values,
+    }
+}
+
+/// Samples a region on the calling thread only.
+pub fn sample_region_serial(region: &Region) -> HeightMap {
+    let mut values = vec![0.; region.width * region.height];
+
+    if region.width > 0 {
+        with_planet(|planet| sample_rows(planet, region, 0, &mut values));
+    }
+
+    HeightMap {
+        width: region.width,
+        height: region.height,
+        values,
+    }
+}
+
+fn sample_rows(planet: &dyn NoiseFn<f64, 3>, region: &Region, first: usize, rows: &mut [f64]) {
+    for (i, row) in rows.chunks_mut(region.width).enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = planet.get(region.point(x, first + i));
+        }
+    }
+}
+
+/// Samples scattered points, the graph is built once for all of them.
+pub fn sample_points(points: &[[f64; 3]]) -> Vec<f64> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    with_planet(|planet| points.iter().map(|point| planet.get(*point)).collect())
+}
+
 pub fn genmap() -> NoiseMap {
+    with_planet(|planet| PlaneMapBuilder::new(planet).build())
+
+    // ImageRenderer::new()
+    //     .set_gradient(ColorGradient::new().build_terrain_gradient())
+    //     .render(&noise_map)
+    //     .write_to_file("unscaledFinalPlanet.png");
+}
+
+/// Builds the planet graph and hands its final node to `f`, the nodes borrow
+/// each other so the graph cannot outlive this call.
+#[allow(non_snake_case)]
+fn with_planet<R>(f: impl FnOnce(&dyn NoiseFn<f64, 3>) -> R) -> R {
     const CURRENT_SEED: u32 = 0;
     const CONTINENT_FREQUENCY: f64 = 1.0;
     const CONTINENT_LACUNARITY: f64 = 2.208984375;
@@ -528,13 +645,5 @@ pub fn genmap() -> NoiseMap {
 
     let unscaledFinalPlanet = Cache::new(continentsWithRivers);
 
-    let noise_map = PlaneMapBuilder::new(&unscaledFinalPlanet).build();
-    // .set_size(1024, 1024)
-
-    noise_map
-
-    // ImageRenderer::new()
-    //     .set_gradient(ColorGradient::new().build_terrain_gradient())
-    //     .render(&noise_map)
-    //     .write_to_file("unscaledFinalPlanet.png");
+    f(&unscaledFinalPlanet)
 }
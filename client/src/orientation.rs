@@ -1,10 +1,14 @@
+use std::convert::TryFrom;
+use std::error::Error;
 use std::f32::consts::PI;
+use std::fmt;
 
-use block_mesh::RIGHT_HANDED_Y_UP_CONFIG;
+use block_mesh::{OrientedBlockFace, QuadCoordinateConfig, RIGHT_HANDED_Y_UP_CONFIG};
 
-use bevy_math::{Quat, Vec2, Vec3};
+use bevy_math::{IVec3, Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum Orientation {
     Top,
     Bottom,
@@ -14,6 +18,145 @@ pub enum Orientation {
     Front,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrientationError {
+    /// Not one of the six axis aligned unit vectors.
+    InvalidNormal([i32; 3]),
+    /// Past the faces of the mesher configuration.
+    InvalidFace(usize),
+}
+
+impl fmt::Display for OrientationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidNormal(normal) => write!(f, "invalid normal {:?}", normal),
+            Self::InvalidFace(index) => write!(f, "invalid face index {}", index),
+        }
+    }
+}
+
+impl Error for OrientationError {}
+
+impl Orientation {
+    pub const ALL: [Orientation; 6] = [
+        Orientation::Left,
+        Orientation::Right,
+        Orientation::Bottom,
+        Orientation::Top,
+        Orientation::Back,
+        Orientation::Front,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Orientation> {
+        Self::ALL.into_iter()
+    }
+
+    /// Position in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Orientation::Left => 0,
+            Orientation::Right => 1,
+            Orientation::Bottom => 2,
+            Orientation::Top => 3,
+            Orientation::Back => 4,
+            Orientation::Front => 5,
+        }
+    }
+
+    pub fn opposite(&self) -> Orientation {
+        match self {
+            Orientation::Top => Orientation::Bottom,
+            Orientation::Bottom => Orientation::Top,
+            Orientation::Left => Orientation::Right,
+            Orientation::Right => Orientation::Left,
+            Orientation::Back => Orientation::Front,
+            Orientation::Front => Orientation::Back,
+        }
+    }
+
+    pub fn normal(&self) -> IVec3 {
+        match self {
+            Orientation::Top => IVec3::Y,
+            Orientation::Bottom => IVec3::NEG_Y,
+            Orientation::Left => IVec3::NEG_X,
+            Orientation::Right => IVec3::X,
+            Orientation::Back => IVec3::NEG_Z,
+            Orientation::Front => IVec3::Z,
+        }
+    }
+
+    /// Horizontal side a direction points to the most.
+    pub fn facing(direction: Vec3) -> Orientation {
+        if direction.x.abs() > direction.z.abs() {
+            if direction.x > 0. {
+                Orientation::Right
+            } else {
+                Orientation::Left
+            }
+        } else if direction.z > 0. {
+            Orientation::Front
+        } else {
+            Orientation::Back
+        }
+    }
+
+    /// Block sharing this face with the one at `position`.
+    pub fn neighbour(&self, position: IVec3) -> IVec3 {
+        position + self.normal()
+    }
+
+    pub fn from_face(face: &OrientedBlockFace) -> Result<Orientation, OrientationError> {
+        let [x, y, z] = face.quad_mesh_normals()[0];
+
+        Orientation::try_from([x as i32, y as i32, z as i32])
+    }
+
+    /// Face of `config` at `index`, its faces may come in any order.
+    pub fn from_face_index(
+        config: &QuadCoordinateConfig,
+        index: usize,
+    ) -> Result<Orientation, OrientationError> {
+        let face = config
+            .faces
+            .get(index)
+            .ok_or(OrientationError::InvalidFace(index))?;
+
+        Orientation::from_face(face)
+    }
+
+    /// Index of the face of `config` pointing this way.
+    pub fn face_index(&self, config: &QuadCoordinateConfig) -> Option<usize> {
+        config
+            .faces
+            .iter()
+            .position(|face| Orientation::from_face(face).ok() == Some(*self))
+    }
+}
+
+impl TryFrom<[i32; 3]> for Orientation {
+    type Error = OrientationError;
+
+    fn try_from(normal: [i32; 3]) -> Result<Self, Self::Error> {
+        match normal {
+            [0, 1, 0] => Ok(Orientation::Top),
+            [0, -1, 0] => Ok(Orientation::Bottom),
+            [-1, 0, 0] => Ok(Orientation::Left),
+            [1, 0, 0] => Ok(Orientation::Right),
+            [0, 0, 1] => Ok(Orientation::Front),
+            [0, 0, -1] => Ok(Orientation::Back),
+            _ => Err(OrientationError::InvalidNormal(normal)),
+        }
+    }
+}
+
+impl TryFrom<IVec3> for Orientation {
+    type Error = OrientationError;
+
+    fn try_from(normal: IVec3) -> Result<Self, Self::Error> {
+        Orientation::try_from(normal.to_array())
+    }
+}
+
 pub fn deg2rand(deg: i32) -> f32 {
     deg as f32 * PI / 180.
 }
@@ -49,22 +192,12 @@ pub fn parse_orientation(minimum: Vec3, size: Vec2, orientation: &Orientation) -
     }
 }
 
-pub fn parse_normal(index: usize, custom: Option<[i32; 3]>) -> Orientation {
-    let normals = RIGHT_HANDED_Y_UP_CONFIG.faces[index].quad_mesh_normals()[0];
-
-    let normal = if let Some(c) = custom {
-        c
-    } else {
-        [normals[0] as i32, normals[1] as i32, normals[2] as i32]
-    };
-
-    match normal {
-        [0, 1, 0] => Orientation::Top,
-        [0, -1, 0] => Orientation::Bottom,
-        [-1, 0, 0] => Orientation::Left,
-        [1, 0, 0] => Orientation::Right,
-        [0, 0, 1] => Orientation::Front,
-        [0, 0, -1] => Orientation::Back,
-        _ => panic!("Invalid normal"),
+pub fn parse_normal(
+    index: usize,
+    custom: Option<[i32; 3]>,
+) -> Result<Orientation, OrientationError> {
+    match custom {
+        Some(normal) => Orientation::try_from(normal),
+        None => Orientation::from_face_index(&RIGHT_HANDED_Y_UP_CONFIG, index),
     }
 }
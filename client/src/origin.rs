@@ -0,0 +1,98 @@
+use bevy_math::{IVec3, Vec3};
+
+use crate::chunk::CHUNK_SIZE;
+use crate::coords::BlockPos;
+
+/// Precise world position, a block and the offset inside it.
+///
+/// `f32` transforms lose precision far from zero, positions that must hold
+/// anywhere on the planet are kept in this form instead.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct WorldPos {
+    pub block: [i64; 3],
+    /// Always within `[0, 1)` on every axis.
+    pub offset: Vec3,
+}
+
+impl WorldPos {
+    pub fn new(block: [i64; 3], offset: Vec3) -> Self {
+        let carry = offset.floor();
+
+        Self {
+            block: [0, 1, 2].map(|axis| block[axis] + carry[axis] as i64),
+            offset: offset - carry,
+        }
+    }
+
+    pub fn translate(&self, delta: Vec3) -> Self {
+        Self::new(self.block, self.offset + delta)
+    }
+
+    /// Block the position is in, `None` past the `i32` range the voxel store
+    /// addresses.
+    pub fn block_pos(&self) -> Option<BlockPos> {
+        let [x, y, z] = self.block.map(i32::try_from);
+
+        Some(BlockPos::new(x.ok()?, y.ok()?, z.ok()?))
+    }
+}
+
+/// Block every Bevy transform is relative to, moved in whole chunks so chunk
+/// meshes stay aligned with the block grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WorldOrigin(pub [i64; 3]);
+
+impl WorldOrigin {
+    /// Precise position of a transform translation.
+    pub fn position(&self, local: Vec3) -> WorldPos {
+        WorldPos::new(self.0, local)
+    }
+
+    /// Transform translation of a precise position.
+    pub fn local(&self, position: &WorldPos) -> Vec3 {
+        let [x, y, z] = [0, 1, 2].map(|axis| (position.block[axis] - self.0[axis]) as f32);
+
+        Vec3::new(x, y, z) + position.offset
+    }
+
+    /// World block, as stored in `VoxelStore`, of a block in transform space.
+    /// Saturated to the `i32` range, blocks past it are never loaded so every
+    /// lookup misses as it would in an unloaded chunk.
+    pub fn to_world(&self, local: IVec3) -> IVec3 {
+        let local = local.to_array();
+
+        IVec3::from([0, 1, 2].map(|axis| {
+            (self.0[axis] + local[axis] as i64).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+        }))
+    }
+
+    /// Block in transform space of a world block.
+    pub fn to_local(&self, world: IVec3) -> IVec3 {
+        let world = world.to_array();
+
+        IVec3::from([0, 1, 2].map(|axis| (world[axis] as i64 - self.0[axis]) as i32))
+    }
+
+    /// New origin under `camera` once it is further than `threshold` from
+    /// the current one on the horizontal plane.
+    pub fn rebased(&self, camera: Vec3, threshold: f32) -> Option<WorldOrigin> {
+        if camera.x.abs() <= threshold && camera.z.abs() <= threshold {
+            return None;
+        }
+
+        let chunks = |axis: f32| (axis / CHUNK_SIZE as f32).floor() as i64 * CHUNK_SIZE as i64;
+
+        Some(WorldOrigin([
+            self.0[0] + chunks(camera.x),
+            self.0[1],
+            self.0[2] + chunks(camera.z),
+        ]))
+    }
+
+    /// Translation to add to every transform when moving to `origin`.
+    pub fn shift(&self, origin: &WorldOrigin) -> Vec3 {
+        let [x, y, z] = [0, 1, 2].map(|axis| (self.0[axis] - origin.0[axis]) as f32);
+
+        Vec3::new(x, y, z)
+    }
+}
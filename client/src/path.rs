@@ -0,0 +1,254 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use bevy_math::IVec3;
+
+const SIDES: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+/// Cost of walking one block, climbing and falling cost extra per block.
+const WALK_COST: u32 = 10;
+const CLIMB_COST: u32 = 5;
+const FALL_COST: u32 = 2;
+
+/// Cells searched before a path is given up on.
+pub const MAX_VISITED: usize = 4096;
+
+pub trait PathWorld {
+    /// `None` when the position is not loaded.
+    fn solid(&self, position: IVec3) -> Option<bool>;
+    fn liquid(&self, position: IVec3) -> bool;
+}
+
+/// Size and moves of whatever walks the path.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Agent {
+    /// Blocks covered along x and z, the path follows the lowest corner.
+    pub width: i32,
+    /// Blocks of headroom needed.
+    pub height: i32,
+    /// Highest ledge climbed in one move.
+    pub step_height: i32,
+    /// Deepest drop taken in one move.
+    pub max_fall: i32,
+    pub avoid_liquid: bool,
+}
+
+impl Agent {
+    /// Agent for a body of the given size in blocks, climbing one block and
+    /// falling three like the player.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width: (width.ceil() as i32).max(1),
+            height: (height.ceil() as i32).max(1),
+            step_height: 1,
+            max_fall: 3,
+            avoid_liquid: true,
+        }
+    }
+
+    fn footprint(&self, feet: IVec3) -> impl Iterator<Item = IVec3> + '_ {
+        (0..self.width).flat_map(move |x| (0..self.width).map(move |z| feet + IVec3::new(x, 0, z)))
+    }
+
+    /// Whether the body fits with its feet in `feet`.
+    fn fits<W: PathWorld>(&self, world: &W, feet: IVec3) -> bool {
+        self.footprint(feet).all(|corner| {
+            (0..self.height).all(|y| {
+                let cell = corner + IVec3::Y * y;
+
+                world.solid(cell) == Some(false) && !(self.avoid_liquid && world.liquid(cell))
+            })
+        })
+    }
+
+    /// Whether the body fits in `feet` with ground under part of it.
+    pub fn stands<W: PathWorld>(&self, world: &W, feet: IVec3) -> bool {
+        self.fits(world, feet)
+            && self
+                .footprint(feet)
+                .any(|corner| world.solid(corner - IVec3::Y) == Some(true))
+    }
+
+    /// Cells reachable in one move from `feet` and their cost: a step to a
+    /// side, climbing a ledge or dropping off one.
+    fn moves<W: PathWorld>(&self, world: &W, feet: IVec3) -> Vec<(IVec3, u32)> {
+        let mut moves = Vec::new();
+
+        for side in SIDES {
+            let ahead = feet + side;
+
+            if self.stands(world, ahead) {
+                moves.push((ahead, WALK_COST));
+            } else if self.fits(world, ahead) {
+                for depth in 1..=self.max_fall {
+                    let below = ahead - IVec3::Y * depth;
+
+                    if !self.fits(world, below) {
+                        break;
+                    }
+
+                    if self.stands(world, below) {
+                        moves.push((below, WALK_COST + FALL_COST * depth as u32));
+                        break;
+                    }
+                }
+            } else {
+                for rise in 1..=self.step_height {
+                    // room to jump where the agent stands before moving over
+                    if !self.fits(world, feet + IVec3::Y * rise) {
+                        break;
+                    }
+
+                    let up = ahead + IVec3::Y * rise;
+
+                    if self.stands(world, up) {
+                        moves.push((up, WALK_COST + CLIMB_COST * rise as u32));
+                        break;
+                    }
+                }
+            }
+        }
+
+        moves
+    }
+}
+
+/// Never more than the cheapest way there, every move goes one block aside.
+fn estimate(from: IVec3, to: IVec3) -> u32 {
+    let distance = (to - from).abs();
+
+    (distance.x + distance.z) as u32 * WALK_COST
+}
+
+/// A* search of the cells the agent stands in from `start` to `goal`, both
+/// included. `None` when the goal is out of reach or more than `max_visited`
+/// cells were searched.
+pub fn find_path<W: PathWorld>(
+    world: &W,
+    agent: &Agent,
+    start: IVec3,
+    goal: IVec3,
+    max_visited: usize,
+) -> Option<Vec<IVec3>> {
+    if !agent.stands(world, start) || !agent.stands(world, goal) {
+        return None;
+    }
+
+    // best cost found to each cell and the cell it was reached from
+    let mut nodes: HashMap<IVec3, (u32, IVec3)> = HashMap::new();
+    let mut closed = HashSet::new();
+    let mut open = BinaryHeap::new();
+
+    nodes.insert(start, (0, start));
+    open.push(Reverse((estimate(start, goal), start.to_array())));
+
+    while let Some(Reverse((_, cell))) = open.pop() {
+        let cell = IVec3::from(cell);
+
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut cell = goal;
+
+            while cell != start {
+                cell = nodes[&cell].1;
+                path.push(cell);
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        if !closed.insert(cell) {
+            continue;
+        }
+
+        if closed.len() > max_visited {
+            return None;
+        }
+
+        let cost = nodes[&cell].0;
+
+        for (next, step) in agent.moves(world, cell) {
+            let cost = cost + step;
+
+            if nodes.get(&next).map_or(true, |(best, _)| cost < *best) {
+                nodes.insert(next, (cost, cell));
+                open.push(Reverse((cost + estimate(next, goal), next.to_array())));
+            }
+        }
+    }
+
+    None
+}
+
+const SOLID: u8 = 1;
+const LIQUID: u8 = 2;
+const LOADED: u8 = 4;
+
+/// Copy of the blocks of a box of the world, so paths can be searched away
+/// from the world they were asked in.
+#[derive(Clone, Debug)]
+pub struct PathGrid {
+    min: IVec3,
+    size: IVec3,
+    cells: Vec<u8>,
+}
+
+impl PathGrid {
+    /// Copies the blocks between `min` and `max` included.
+    pub fn capture<W: PathWorld>(world: &W, min: IVec3, max: IVec3) -> Self {
+        // nothing to walk on below the world
+        let min = min.max(IVec3::new(min.x, 0, min.z));
+        let size = (max - min + IVec3::ONE).max(IVec3::ZERO);
+
+        let mut cells = Vec::with_capacity((size.x * size.y * size.z) as usize);
+
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let position = min + IVec3::new(x, y, z);
+
+                    cells.push(match world.solid(position) {
+                        Some(solid) => {
+                            LOADED
+                                | if solid { SOLID } else { 0 }
+                                | if world.liquid(position) { LIQUID } else { 0 }
+                        }
+                        None => 0,
+                    });
+                }
+            }
+        }
+
+        Self { min, size, cells }
+    }
+
+    /// Box around two cells with `margin` blocks to spare on every side.
+    pub fn around<W: PathWorld>(world: &W, start: IVec3, goal: IVec3, margin: i32) -> Self {
+        let margin = IVec3::splat(margin);
+
+        Self::capture(world, start.min(goal) - margin, start.max(goal) + margin)
+    }
+
+    fn cell(&self, position: IVec3) -> u8 {
+        let local = position - self.min;
+
+        if local.cmplt(IVec3::ZERO).any() || local.cmpge(self.size).any() {
+            return 0;
+        }
+
+        self.cells[((local.x * self.size.y + local.y) * self.size.z + local.z) as usize]
+    }
+}
+
+impl PathWorld for PathGrid {
+    fn solid(&self, position: IVec3) -> Option<bool> {
+        let cell = self.cell(position);
+
+        (cell & LOADED != 0).then_some(cell & SOLID != 0)
+    }
+
+    fn liquid(&self, position: IVec3) -> bool {
+        self.cell(position) & LIQUID != 0
+    }
+}
@@ -0,0 +1,227 @@
+use bevy_math::{BVec3, IVec3, Vec3};
+
+const EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Box standing on `feet`, centered horizontally.
+    pub fn from_feet(feet: Vec3, half_width: f32, height: f32) -> Self {
+        Self {
+            min: feet - Vec3::new(half_width, 0., half_width),
+            max: feet + Vec3::new(half_width, height, half_width),
+        }
+    }
+
+    pub fn translate(&self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Grows the box along `axis` to cover a movement of `delta`.
+    pub fn extend(&self, axis: usize, delta: f32) -> Self {
+        let mut aabb = *self;
+
+        if delta > 0. {
+            aabb.max[axis] += delta;
+        } else {
+            aabb.min[axis] += delta;
+        }
+
+        aabb
+    }
+
+    /// Every block position the box touches.
+    pub fn blocks(&self) -> impl Iterator<Item = IVec3> {
+        let min = self.min.floor().as_ivec3();
+        let max = self.max.floor().as_ivec3();
+
+        (min.x..=max.x).flat_map(move |x| {
+            (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z)))
+        })
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] < other.max[axis] && self.max[axis] > other.min[axis])
+    }
+}
+
+fn clip_axis<F: Fn(IVec3) -> bool>(aabb: &Aabb, axis: usize, delta: f32, solid: &F) -> f32 {
+    if delta == 0. {
+        return 0.;
+    }
+
+    let mut delta = delta;
+
+    for block in aabb.extend(axis, delta).blocks() {
+        if !solid(block) {
+            continue;
+        }
+
+        let min = block.as_vec3();
+        let max = min + Vec3::ONE;
+
+        let overlaps = (0..3)
+            .filter(|other| *other != axis)
+            .all(|other| aabb.min[other] < max[other] && aabb.max[other] > min[other]);
+
+        if !overlaps {
+            continue;
+        }
+
+        if delta > 0. && aabb.max[axis] <= min[axis] + EPSILON {
+            delta = delta.min(min[axis] - aabb.max[axis]);
+        } else if delta < 0. && aabb.min[axis] >= max[axis] - EPSILON {
+            delta = delta.max(max[axis] - aabb.min[axis]);
+        }
+    }
+
+    delta
+}
+
+/// Moves `aabb` by `motion` one axis at a time (Y, X then Z), stopping at the
+/// first solid block on each axis. Returns the applied offset and which axes
+/// were blocked.
+pub fn sweep<F: Fn(IVec3) -> bool>(aabb: &Aabb, motion: Vec3, solid: F) -> (Vec3, BVec3) {
+    let mut moved = *aabb;
+    let mut offset = Vec3::ZERO;
+    let mut hit = [false; 3];
+
+    for axis in [1, 0, 2] {
+        let delta = clip_axis(&moved, axis, motion[axis], &solid);
+
+        hit[axis] = (delta - motion[axis]).abs() > EPSILON;
+        offset[axis] = delta;
+
+        let mut step = Vec3::ZERO;
+        step[axis] = delta;
+        moved = moved.translate(step);
+    }
+
+    (offset, BVec3::new(hit[0], hit[1], hit[2]))
+}
+
+/// Same as `sweep`, but when a grounded box is stopped horizontally it also
+/// tries to climb up to `step_height` and keeps whichever move went further.
+pub fn sweep_step<F: Fn(IVec3) -> bool>(
+    aabb: &Aabb,
+    motion: Vec3,
+    step_height: f32,
+    on_ground: bool,
+    solid: F,
+) -> (Vec3, BVec3) {
+    let (offset, hit) = sweep(aabb, motion, &solid);
+
+    if !on_ground || !(hit.x || hit.z) || step_height <= 0. {
+        return (offset, hit);
+    }
+
+    let up = clip_axis(aabb, 1, step_height, &solid);
+    let raised = aabb.translate(Vec3::Y * up);
+
+    let (horizontal, stepped_hit) = sweep(&raised, Vec3::new(motion.x, 0., motion.z), &solid);
+    let moved = raised.translate(horizontal);
+    let fall = -up + motion.y.min(0.);
+    let down = clip_axis(&moved, 1, fall, &solid);
+
+    let flat = offset.x * offset.x + offset.z * offset.z;
+    let climbed = horizontal.x * horizontal.x + horizontal.z * horizontal.z;
+
+    if climbed <= flat + EPSILON {
+        return (offset, hit);
+    }
+
+    (
+        Vec3::new(horizontal.x, up + down, horizontal.z),
+        BVec3::new(stepped_hit.x, (down - fall).abs() > EPSILON, stepped_hit.z),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayHit {
+    pub block: IVec3,
+    /// Face of `block` the ray entered through, zero if it started inside.
+    pub normal: IVec3,
+    pub distance: f32,
+}
+
+/// Walks the blocks crossed by the ray (Amanatides & Woo) and returns the
+/// first solid one closer than `max_distance`.
+pub fn raycast<F: Fn(IVec3) -> bool>(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    solid: F,
+) -> Option<RayHit> {
+    let direction = direction.normalize_or_zero();
+
+    if direction == Vec3::ZERO {
+        return None;
+    }
+
+    let mut block = origin.floor().as_ivec3();
+
+    if solid(block) {
+        return Some(RayHit {
+            block,
+            normal: IVec3::ZERO,
+            distance: 0.,
+        });
+    }
+
+    let mut step = IVec3::ZERO;
+    let mut next = Vec3::splat(f32::INFINITY);
+    let mut delta = Vec3::splat(f32::INFINITY);
+
+    for axis in 0..3 {
+        if direction[axis] > 0. {
+            step[axis] = 1;
+            next[axis] = (block[axis] as f32 + 1. - origin[axis]) / direction[axis];
+            delta[axis] = 1. / direction[axis];
+        } else if direction[axis] < 0. {
+            step[axis] = -1;
+            next[axis] = (block[axis] as f32 - origin[axis]) / direction[axis];
+            delta[axis] = -1. / direction[axis];
+        }
+    }
+
+    loop {
+        let axis = if next.x < next.y && next.x < next.z {
+            0
+        } else if next.y < next.z {
+            1
+        } else {
+            2
+        };
+
+        let distance = next[axis];
+
+        if distance > max_distance {
+            return None;
+        }
+
+        block[axis] += step[axis];
+        next[axis] += delta[axis];
+
+        if solid(block) {
+            let mut normal = IVec3::ZERO;
+            normal[axis] = -step[axis];
+
+            return Some(RayHit {
+                block,
+                normal,
+                distance,
+            });
+        }
+    }
+}
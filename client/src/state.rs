@@ -0,0 +1,211 @@
+use bevy_math::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+
+use crate::orientation::Orientation;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Half {
+    Bottom,
+    Top,
+}
+
+/// State properties a block carries, the others keep their default value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateProperties {
+    /// Horizontal facing, the model front turned toward it.
+    pub facing: bool,
+    /// Axis the model Y axis is laid along.
+    pub axis: bool,
+    /// Upper or lower half, the model flipped upside down on top.
+    pub half: bool,
+}
+
+/// Facing in bits 0 to 2, axis in bits 3 and 4 and half in bit 5.
+///
+/// The default state faces `Front` along the Y axis on the bottom half, which
+/// is the model as authored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct BlockState(pub u8);
+
+const FACINGS: [Orientation; 6] = [
+    Orientation::Front,
+    Orientation::Right,
+    Orientation::Back,
+    Orientation::Left,
+    Orientation::Top,
+    Orientation::Bottom,
+];
+
+const AXES: [Axis; 3] = [Axis::Y, Axis::X, Axis::Z];
+
+impl BlockState {
+    pub fn facing(&self) -> Orientation {
+        FACINGS
+            .get((self.0 & 0b111) as usize)
+            .copied()
+            .unwrap_or(Orientation::Front)
+    }
+
+    pub fn with_facing(self, facing: Orientation) -> Self {
+        let index = FACINGS.iter().position(|f| *f == facing).unwrap_or(0) as u8;
+
+        Self(self.0 & !0b111 | index)
+    }
+
+    pub fn axis(&self) -> Axis {
+        AXES.get(((self.0 >> 3) & 0b11) as usize)
+            .copied()
+            .unwrap_or(Axis::Y)
+    }
+
+    pub fn with_axis(self, axis: Axis) -> Self {
+        let index = AXES.iter().position(|a| *a == axis).unwrap_or(0) as u8;
+
+        Self(self.0 & !0b11000 | index << 3)
+    }
+
+    pub fn half(&self) -> Half {
+        if self.0 & 0b100000 == 0 {
+            Half::Bottom
+        } else {
+            Half::Top
+        }
+    }
+
+    pub fn with_half(self, half: Half) -> Self {
+        match half {
+            Half::Bottom => Self(self.0 & !0b100000),
+            Half::Top => Self(self.0 | 0b100000),
+        }
+    }
+
+    /// Model to world transform of the state, facing then axis then half.
+    pub fn apply(&self, vector: IVec3) -> IVec3 {
+        let mut vector = vector;
+
+        if self.half() == Half::Top {
+            vector.y = -vector.y;
+        }
+
+        vector = match self.axis() {
+            Axis::Y => vector,
+            Axis::X => IVec3::new(vector.y, -vector.x, vector.z),
+            Axis::Z => IVec3::new(vector.x, -vector.z, vector.y),
+        };
+
+        for _ in 0..quarter_turns(self.facing()) {
+            vector = IVec3::new(vector.z, vector.y, -vector.x);
+        }
+
+        vector
+    }
+
+    /// World to model transform, the inverse of `apply`.
+    pub fn invert(&self, vector: IVec3) -> IVec3 {
+        let mut vector = vector;
+
+        for _ in 0..quarter_turns(self.facing()) {
+            vector = IVec3::new(-vector.z, vector.y, vector.x);
+        }
+
+        vector = match self.axis() {
+            Axis::Y => vector,
+            Axis::X => IVec3::new(-vector.y, vector.x, vector.z),
+            Axis::Z => IVec3::new(vector.x, vector.z, -vector.y),
+        };
+
+        if self.half() == Half::Top {
+            vector.y = -vector.y;
+        }
+
+        vector
+    }
+
+    /// Face of the model drawn on the `world` face of the block, and the
+    /// quarter turns its texture is rotated by to follow the model.
+    pub fn model_face(&self, world: Orientation) -> (Orientation, u8) {
+        let model = Orientation::try_from(self.invert(world.normal())).unwrap_or(world);
+
+        let target = self.apply(texture_up(model));
+        let mut up = texture_up(world);
+
+        for turns in 0..4 {
+            if up == target {
+                return (model, turns);
+            }
+
+            up = world.normal().cross(up);
+        }
+
+        // the half flip mirrors instead of rotating, keep the texture upright
+        (model, 0)
+    }
+}
+
+/// Quarter turns around Y taking the model front to `facing`.
+fn quarter_turns(facing: Orientation) -> u8 {
+    match facing {
+        Orientation::Front => 0,
+        Orientation::Right => 1,
+        Orientation::Back => 2,
+        Orientation::Left => 3,
+        Orientation::Top | Orientation::Bottom => 0,
+    }
+}
+
+/// Direction the top of a face texture points to.
+fn texture_up(face: Orientation) -> IVec3 {
+    match face {
+        Orientation::Top => IVec3::NEG_Z,
+        Orientation::Bottom => IVec3::Z,
+        _ => IVec3::Y,
+    }
+}
+
+/// State of a block placed against the `normal` face of another, looking
+/// along `look`, `height` being how far up its cell the ray hit.
+pub fn placement(
+    properties: StateProperties,
+    look: Vec3,
+    normal: IVec3,
+    height: f32,
+) -> BlockState {
+    let mut state = BlockState::default();
+
+    if properties.facing {
+        // the front turns toward the player
+        state = state.with_facing(Orientation::facing(look).opposite());
+    }
+
+    if properties.axis {
+        let axis = match normal.abs().to_array() {
+            [1, 0, 0] => Axis::X,
+            [0, 0, 1] => Axis::Z,
+            _ => Axis::Y,
+        };
+
+        state = state.with_axis(axis);
+    }
+
+    if properties.half {
+        let top = match normal.y {
+            -1 => true,
+            1 => false,
+            _ => height.rem_euclid(1.) > 0.5,
+        };
+
+        state = state.with_half(if top { Half::Top } else { Half::Bottom });
+    }
+
+    state
+}
@@ -0,0 +1,44 @@
+use std::f32::consts::TAU;
+
+use bevy_math::Vec3;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WorldTime {
+    pub day: u32,
+    /// Fraction of the day elapsed, 0 is midnight and 0.5 is noon.
+    pub time_of_day: f32,
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            time_of_day: 0.3,
+        }
+    }
+}
+
+impl WorldTime {
+    pub fn advance(&mut self, seconds: f32, day_length: f32) {
+        let time = self.time_of_day + seconds / day_length;
+
+        self.day += time.floor() as u32;
+        self.time_of_day = time.fract();
+    }
+
+    /// Unit vector pointing to the sun, rising on +X and setting on -X.
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = (self.time_of_day - 0.25) * TAU;
+
+        Vec3::new(angle.cos(), angle.sin(), 0.)
+    }
+
+    /// 0 at night, 1 during the day, smoothed around sunrise and sunset.
+    pub fn daylight(&self) -> f32 {
+        let t = ((self.sun_direction().y + 0.1) / 0.3).clamp(0., 1.);
+
+        t * t * (3. - 2. * t)
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy_math::{IVec3, Mat4, Vec3, Vec4};
+
+use crate::block::BlockRegistry;
+use crate::chunk::{ChunkData, CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::coords::BlockPos;
+
+pub const SECTION_SIZE: i32 = 16;
+pub const SECTIONS: i32 = (CHUNK_HEIGHT + SECTION_SIZE - 1) / SECTION_SIZE;
+
+/// Section faces, the opposite of face `i` is `i ^ 1`.
+pub const FACES: [IVec3; 6] = [
+    IVec3::NEG_X,
+    IVec3::X,
+    IVec3::NEG_Y,
+    IVec3::Y,
+    IVec3::NEG_Z,
+    IVec3::Z,
+];
+
+fn opposite(face: usize) -> usize {
+    face ^ 1
+}
+
+/// Which pairs of faces of a section are connected through non opaque voxels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SectionVisibility(u64);
+
+impl SectionVisibility {
+    pub fn all() -> Self {
+        Self((1 << 36) - 1)
+    }
+
+    pub fn connects(&self, a: usize, b: usize) -> bool {
+        self.0 & (1 << (a * 6 + b)) != 0
+    }
+
+    fn connect(&mut self, a: usize, b: usize) {
+        self.0 |= 1 << (a * 6 + b);
+        self.0 |= 1 << (b * 6 + a);
+    }
+}
+
+/// Height of a section, the topmost one is cut by the world height.
+pub fn section_height(section: i32) -> i32 {
+    (CHUNK_HEIGHT - section * SECTION_SIZE).min(SECTION_SIZE)
+}
+
+/// Flood fills the non opaque voxels of a section to find which of its faces
+/// can see each other, `opaque` takes positions local to the section.
+pub fn section_visibility<F: Fn(IVec3) -> bool>(height: i32, opaque: F) -> SectionVisibility {
+    let size = IVec3::new(SECTION_SIZE, height, SECTION_SIZE);
+    let index = |p: IVec3| ((p.y * SECTION_SIZE + p.z) * SECTION_SIZE + p.x) as usize;
+
+    let mut visited = vec![false; (SECTION_SIZE * SECTION_SIZE * height) as usize];
+    let mut visibility = SectionVisibility::default();
+
+    for start in (0..height).flat_map(|y| {
+        (0..SECTION_SIZE).flat_map(move |z| (0..SECTION_SIZE).map(move |x| IVec3::new(x, y, z)))
+    }) {
+        if visited[index(start)] || opaque(start) {
+            continue;
+        }
+
+        visited[index(start)] = true;
+
+        let mut touched = [false; 6];
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(position) = queue.pop_front() {
+            for (face, direction) in FACES.iter().enumerate() {
+                let neighbour = position + *direction;
+
+                let inside = neighbour.cmpge(IVec3::ZERO).all() && neighbour.cmplt(size).all();
+
+                if !inside {
+                    touched[face] = true;
+                    continue;
+                }
+
+                if visited[index(neighbour)] || opaque(neighbour) {
+                    continue;
+                }
+
+                visited[index(neighbour)] = true;
+                queue.push_back(neighbour);
+            }
+        }
+
+        for a in 0..6 {
+            for b in 0..6 {
+                if touched[a] && touched[b] {
+                    visibility.connect(a, b);
+                }
+            }
+        }
+    }
+
+    visibility
+}
+
+/// Visibility graph of every section of a chunk, bottom to top.
+pub fn chunk_visibility(chunk: &ChunkData, registry: &BlockRegistry) -> Vec<SectionVisibility> {
+    (0..SECTIONS)
+        .map(|section| {
+            let base = IVec3::new(0, section * SECTION_SIZE, 0);
+
+            section_visibility(section_height(section), |local| {
+                registry.get(chunk.block(base + local)).opaque
+            })
+        })
+        .collect()
+}
+
+/// View frustum side and near planes, as `normal.xyz` and `distance.w`.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    planes: [Vec4; 5],
+}
+
+impl Frustum {
+    /// Extracts the planes of a reversed z projection times view matrix.
+    pub fn from_matrix(view_projection: Mat4) -> Self {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|i| view_projection.row(i));
+
+        Self {
+            planes: [w + x, w - x, w + y, w - y, w - z],
+        }
+    }
+
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), max, min);
+
+            normal.dot(corner) + plane.w >= 0.
+        })
+    }
+}
+
+pub fn section_aabb(section: IVec3) -> (Vec3, Vec3) {
+    let min = IVec3::new(
+        section.x * CHUNK_SIZE,
+        section.y * SECTION_SIZE,
+        section.z * CHUNK_SIZE,
+    )
+    .as_vec3();
+
+    let max = min
+        + Vec3::new(
+            CHUNK_SIZE as f32,
+            section_height(section.y) as f32,
+            CHUNK_SIZE as f32,
+        );
+
+    (min, max)
+}
+
+/// Sections visible from the `camera` block, walking outward through sections
+/// whose faces connect. `graph` returns `None` for unloaded sections.
+///
+/// A camera in an unloaded chunk has no section to walk from, every loaded
+/// section in view is visible then.
+pub fn visible_sections<G, F>(
+    camera: BlockPos,
+    max_distance: i32,
+    graph: G,
+    in_view: F,
+) -> HashSet<IVec3>
+where
+    G: Fn(IVec3) -> Option<SectionVisibility>,
+    F: Fn(IVec3) -> bool,
+{
+    let chunk = camera.chunk().0;
+
+    let start = IVec3::new(
+        chunk.x,
+        camera.0.y.div_euclid(SECTION_SIZE).clamp(0, SECTIONS - 1),
+        chunk.y,
+    );
+
+    if graph(start).is_none() {
+        return (-max_distance..=max_distance)
+            .flat_map(|x| (-max_distance..=max_distance).map(move |z| (x, z)))
+            .flat_map(|(x, z)| (0..SECTIONS).map(move |y| IVec3::new(chunk.x + x, y, chunk.y + z)))
+            .filter(|section| graph(*section).is_some() && in_view(*section))
+            .collect();
+    }
+
+    let mut visible = HashSet::new();
+
+    // section, face it was entered through and directions walked so far
+    let mut queue = VecDeque::from([(start, None::<usize>, 0_u8)]);
+    visible.insert(start);
+
+    while let Some((section, entered, walked)) = queue.pop_front() {
+        let connections = match graph(section) {
+            Some(connections) => connections,
+            None => continue,
+        };
+
+        for (face, direction) in FACES.iter().enumerate() {
+            // never walk back toward the camera
+            if walked & (1 << opposite(face)) != 0 {
+                continue;
+            }
+
+            if let Some(entered) = entered {
+                if !connections.connects(entered, face) {
+                    continue;
+                }
+            }
+
+            let neighbour = section + *direction;
+
+            if neighbour.y < 0
+                || neighbour.y >= SECTIONS
+                || (neighbour.x - start.x).abs() > max_distance
+                || (neighbour.z - start.z).abs() > max_distance
+                || visible.contains(&neighbour)
+                || graph(neighbour).is_none()
+                || !in_view(neighbour)
+            {
+                continue;
+            }
+
+            visible.insert(neighbour);
+            queue.push_back((neighbour, Some(opposite(face)), walked | 1 << face));
+        }
+    }
+
+    visible
+}